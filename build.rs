@@ -0,0 +1,25 @@
+use std::env;
+use std::process::Command;
+
+// sets MILESTONE_VERSION, the code version experiments are recorded with:
+// the crate's version, and the commit it's built from when built from git
+fn main() {
+    let version = env::var("CARGO_PKG_VERSION").unwrap();
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        });
+
+    match commit {
+        Some(commit) => {
+            println!("cargo:rustc-env=MILESTONE_VERSION={version}+{commit}")
+        }
+        None => println!("cargo:rustc-env=MILESTONE_VERSION={version}"),
+    }
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+}
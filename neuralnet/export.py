@@ -0,0 +1,22 @@
+import json
+import sys
+
+import joblib
+
+
+def export_nn(regr_path, json_path):
+    regr = joblib.load(regr_path)
+
+    model = {
+        "activation": regr.activation,
+        "out_activation": regr.out_activation_,
+        "coefs": [layer.tolist() for layer in regr.coefs_],
+        "intercepts": [layer.tolist() for layer in regr.intercepts_],
+    }
+    with open(json_path, "w") as f:
+        json.dump(model, f)
+
+
+if __name__ == "__main__":
+    # python neuralnet/export.py neuralnet/exp2.joblib neuralnet/exp2.json
+    export_nn(sys.argv[1], sys.argv[2])
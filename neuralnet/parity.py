@@ -0,0 +1,39 @@
+import json
+import sys
+
+import numpy
+import pandas
+from sklearn.neural_network import MLPRegressor
+
+from predict import transform_state
+
+
+def load_nn(model):
+    regr = MLPRegressor(activation=model["activation"])
+    regr.coefs_ = [numpy.array(layer) for layer in model["coefs"]]
+    regr.intercepts_ = [numpy.array(layer) for layer in model["intercepts"]]
+    regr.out_activation_ = model["out_activation"]
+    regr.n_layers_ = len(regr.coefs_) + 1
+    regr.n_outputs_ = 1
+    regr.n_features_in_ = 37
+    return regr
+
+
+def refresh_predictions(fixture_path):
+    with open(fixture_path) as f:
+        fixture = json.load(f)
+
+    regr = load_nn(fixture["model"])
+    states = [transform_state(case["state"]) for case in fixture["cases"]]
+    state_input = pandas.DataFrame(numpy.reshape(states, (len(states), 37)))
+    for case, prediction in zip(fixture["cases"], regr.predict(state_input)):
+        case["prediction"] = float(prediction)
+
+    with open(fixture_path, "w") as f:
+        json.dump(fixture, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    # python neuralnet/parity.py tests/data/mlp_parity.json
+    refresh_predictions(sys.argv[1])
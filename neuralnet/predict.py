@@ -4,9 +4,7 @@ import pandas
 import sklearn
 from sklearn.neural_network import MLPRegressor
 
-def run_nn(state_repr, regr_path):
-    regr = joblib.load(regr_path)
-
+def transform_state(state_repr):
     state = []
     for i in state_repr[2:]:
         if i == 'b':
@@ -18,10 +16,21 @@ def run_nn(state_repr, regr_path):
         else:
             for j in range(int(i)):
                 state.append(0)
+    return state
 
-    state_input = numpy.reshape(state, (1,37))
+def run_nn(state_repr, regr_path):
+    regr = joblib.load(regr_path)
+
+    state_input = numpy.reshape(transform_state(state_repr), (1,37))
     return float(regr.predict(pandas.DataFrame(state_input)))
 
+def run_nn_batch(state_reprs, regr_path):
+    regr = joblib.load(regr_path)
+
+    states = [transform_state(state_repr) for state_repr in state_reprs]
+    state_input = numpy.reshape(states, (len(states),37))
+    return [float(x) for x in regr.predict(pandas.DataFrame(state_input))]
+
 
 if __name__ == "__main__":
     run_nn("b:b/bb/bbb/bbbb/3/4/3/4/3/wwww/www/ww/w", "neuralnet/nn.joblib")
@@ -0,0 +1,299 @@
+use lazy_static::lazy_static;
+use log::{info, warn};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rusqlite::{Connection, Result};
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::game::board::Move;
+use crate::game::gamestate::State;
+
+/// how many plies from the start a book covers unless told otherwise
+pub const DEFAULT_MAX_PLIES: u8 = 8;
+
+/// positions seen in fewer games than this are left out of a book
+pub const DEFAULT_MIN_GAMES: u32 = 3;
+
+const MAGIC: &[u8; 4] = b"MSOB";
+const VERSION: u8 = 1;
+
+lazy_static! {
+    /// the opening book at `OPENING_BOOK_PATH`, if there is one
+    pub static ref OPENING_BOOK: Option<OpeningBook> =
+        env::var("OPENING_BOOK_PATH").ok().and_then(|path| {
+            match OpeningBook::load(&path) {
+                Ok(book) => {
+                    info!(
+                        "Using the opening book at {path} ({} positions)",
+                        book.entries.len()
+                    );
+                    Some(book)
+                }
+                Err(e) => {
+                    warn!("Could not load the opening book at {path} ({e})");
+                    None
+                }
+            }
+        });
+    static ref BOOK_SELECTION: BookSelection =
+        match env::var("BOOK_SELECTION").as_deref() {
+            Ok(s) => match s.parse() {
+                Ok(selection) => {
+                    info!("Using BOOK_SELECTION environment variable ({s})");
+                    selection
+                }
+                Err(e) => {
+                    warn!("Not using the opening book ({e})");
+                    BookSelection::Off
+                }
+            },
+            Err(_) => BookSelection::Off,
+        };
+}
+
+/// how a move is picked among the ones the book knows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BookSelection {
+    // the book isn't used, as in the games that measure an agent's fitness
+    #[default]
+    Off,
+    // the move with the best record for the player moving
+    Best,
+    // a random move, as likely as the number of games it won for the player
+    // moving, so self-play doesn't keep repeating the same opening
+    Weighted,
+}
+
+impl BookSelection {
+    // from_env() is the selection for the games that can use the book, set
+    // with BOOK_SELECTION (off unless it's set)
+    pub fn from_env() -> Self {
+        *BOOK_SELECTION
+    }
+}
+
+// `off`, `best` or `weighted`
+impl FromStr for BookSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(BookSelection::Off),
+            "best" => Ok(BookSelection::Best),
+            "weighted" => Ok(BookSelection::Weighted),
+            _ => Err(format!("{s} isn't a book selection")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookEntry {
+    pub games: u32,
+    pub black_wins: u32,
+}
+
+impl BookEntry {
+    // the fraction of games won by `player`
+    pub fn win_rate(&self, player: u8) -> f64 {
+        let black_rate = self.black_wins as f64 / self.games as f64;
+        match player {
+            0 => black_rate,
+            _ => 1.0 - black_rate,
+        }
+    }
+}
+
+/// a move the book suggests, with the games played after it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookMove {
+    pub mv: Move,
+    pub games: u32,
+    // the fraction of those games won by the player making the move
+    pub win_rate: f64,
+}
+
+/// how the games in the database went from every position reached in their
+/// first few plies, keyed by the position's zobrist hash (which is the same
+/// from one run to the next)
+///
+/// on disk, a book is `MSOB`, a version byte, the number of plies covered and
+/// the number of positions (a little-endian u32), followed by each position's
+/// hash, games and wins for black (u64, u32, u32, little-endian) in order of
+/// hash
+pub struct OpeningBook {
+    max_plies: u8,
+    // sorted by hash, for binary search
+    entries: Vec<(u64, BookEntry)>,
+}
+
+impl OpeningBook {
+    // build() goes through every game in `state_table` and `game_table`,
+    // counting the positions in their first `max_plies` plies
+    pub fn build(
+        conn: &Connection,
+        max_plies: u8,
+        min_games: u32,
+    ) -> Result<OpeningBook> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT state_table.state, game_table.result
+            FROM state_table
+            JOIN game_table ON state_table.game_id = game_table.game_id
+            WHERE state_table.move_number <= ?1 AND state_table.state != ''
+            "#,
+        )?;
+        let rows = stmt.query_map([max_plies], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u8>(1)?))
+        })?;
+
+        let mut counts: HashMap<u64, BookEntry> = HashMap::new();
+        for row in rows {
+            let (repr, result) = row?;
+            let Ok(state) = State::from_repr_string(&repr) else {
+                warn!("Skipping a state that couldn't be read ({repr})");
+                continue;
+            };
+            let entry = counts.entry(state.hash()).or_insert(BookEntry {
+                games: 0,
+                black_wins: 0,
+            });
+            entry.games += 1;
+            if result == 0 {
+                entry.black_wins += 1;
+            }
+        }
+
+        let mut entries: Vec<(u64, BookEntry)> = counts
+            .into_iter()
+            .filter(|(_, entry)| entry.games >= min_games)
+            .collect();
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        Ok(OpeningBook { max_plies, entries })
+    }
+
+    pub fn max_plies(&self) -> u8 {
+        self.max_plies
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, state: &State) -> Option<BookEntry> {
+        let key = state.hash();
+        self.entries
+            .binary_search_by_key(&key, |&(k, _)| k)
+            .ok()
+            .map(|idx| self.entries[idx].1)
+    }
+
+    // book_moves() gives every legal move that leads to a position in the
+    // book, best first
+    pub fn book_moves(&self, state: &mut State) -> Vec<BookMove> {
+        let player = state.current_turn;
+        let mut moves: Vec<BookMove> = state
+            .current_possible_moves(player)
+            .iter()
+            .filter_map(|&mv| {
+                let undo = state.make_move(mv);
+                let entry = self.get(state);
+                state.unmake_move(undo);
+                entry.map(|entry| BookMove {
+                    mv,
+                    games: entry.games,
+                    win_rate: entry.win_rate(player),
+                })
+            })
+            .collect();
+        moves.sort_by(|a, b| {
+            b.win_rate
+                .total_cmp(&a.win_rate)
+                .then(b.games.cmp(&a.games))
+        });
+        moves
+    }
+
+    // probe() picks a move for the player to move, if the book has any and
+    // the move's position is within the plies it covers. moves that never
+    // won are left to the search instead
+    pub fn probe<R: Rng>(
+        &self,
+        state: &mut State,
+        selection: BookSelection,
+        rng: &mut R,
+    ) -> Option<BookMove> {
+        if selection == BookSelection::Off
+            || state.plies >= self.max_plies as usize
+        {
+            return None;
+        }
+        let moves: Vec<BookMove> = self
+            .book_moves(state)
+            .into_iter()
+            .filter(|m| m.win_rate > 0.0)
+            .collect();
+        match selection {
+            BookSelection::Off => None,
+            BookSelection::Best => moves.first().copied(),
+            BookSelection::Weighted => moves
+                .choose_weighted(rng, |m| m.win_rate * m.games as f64)
+                .ok()
+                .copied(),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION, self.max_plies])?;
+        file.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+        for (key, entry) in &self.entries {
+            file.write_all(&key.to_le_bytes())?;
+            file.write_all(&entry.games.to_le_bytes())?;
+            file.write_all(&entry.black_wins.to_le_bytes())?;
+        }
+        file.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<OpeningBook> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut header = [0; 10];
+        file.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a milestone opening book (or from another version)",
+            ));
+        }
+
+        let max_plies = header[5];
+        let len = u32::from_le_bytes(header[6..10].try_into().unwrap());
+        let mut entries = Vec::with_capacity(len as usize);
+        let mut record = [0; 16];
+        for _ in 0..len {
+            file.read_exact(&mut record)?;
+            let key = u64::from_le_bytes(record[..8].try_into().unwrap());
+            let games = u32::from_le_bytes(record[8..12].try_into().unwrap());
+            let black_wins =
+                u32::from_le_bytes(record[12..].try_into().unwrap());
+            entries.push((key, BookEntry { games, black_wins }));
+        }
+        if !entries.windows(2).all(|w| w[0].0 < w[1].0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the opening book's positions are out of order",
+            ));
+        }
+        Ok(OpeningBook { max_plies, entries })
+    }
+}
@@ -0,0 +1,60 @@
+use crate::game::board::Move;
+use crate::game::gamestate::State;
+
+use super::heuristics::HeuristicWeights;
+use super::nn::NnModel;
+
+/// scores the positions at the leaves of a search, from black's point of
+/// view: positive when black is ahead, negative when white is, and
+/// (roughly) the negation when the colors are swapped
+pub trait Evaluator: Send + Sync {
+    fn score(&self, state: &State) -> f64;
+
+    fn score_batch(&self, states: &[State]) -> Vec<f64> {
+        states.iter().map(|state| self.score(state)).collect()
+    }
+
+    // why the evaluator likes the position at the end of `line`, for the
+    // trace log
+    fn explain(&self, state: &State, line: &[Move]) -> String;
+}
+
+// the search looks positions up in the tablebase itself (unless its config
+// says not to)
+impl Evaluator for HeuristicWeights {
+    fn score(&self, state: &State) -> f64 {
+        self.heuristic_score(state)
+    }
+
+    fn explain(&self, state: &State, line: &[Move]) -> String {
+        format!(
+            "{:#?}",
+            self.new_with_state_and_moves(state.clone(), &line.to_vec())
+        )
+    }
+}
+
+// the network's chance of black winning, moved to between -1 and 1
+impl Evaluator for NnModel {
+    fn score(&self, state: &State) -> f64 {
+        2.0 * self.evaluate(state) - 1.0
+    }
+
+    fn score_batch(&self, states: &[State]) -> Vec<f64> {
+        self.evaluate_batch(states)
+            .into_iter()
+            .map(|p| 2.0 * p - 1.0)
+            .collect()
+    }
+
+    fn explain(&self, state: &State, line: &[Move]) -> String {
+        let mut end = state.clone();
+        for &m in line {
+            end.make_move(m);
+        }
+        format!(
+            "{self:?} gives black a {:.1}% chance of winning",
+            100.0 * self.evaluate(&end)
+        )
+    }
+}
@@ -0,0 +1,207 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rusqlite::{Connection, Result};
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::game::bitboard::NUM_HEXES;
+use crate::game::gamestate::State;
+
+use super::heuristics::{HeuristicWeights, NUM_HEURISTICS};
+use super::mlp::encode;
+
+/// a distinct position and how the games it came up in went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportRow {
+    pub state: String,
+    pub games: u32,
+    pub black_wins: u32,
+}
+
+impl ExportRow {
+    // the training target, as in `neuralnet/train.py`
+    pub fn black_win_ratio(&self) -> f64 {
+        self.black_wins as f64 / self.games as f64
+    }
+}
+
+/// what goes into an export besides the board encoding and the target
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ExportOptions {
+    // the score of every heuristic, unweighted
+    pub heuristics: bool,
+    // every game counted a second time, reflected left to right
+    pub mirror: bool,
+}
+
+// load_split() loads the positions of every game in `dataset` (or every
+// game), and splits them into training and test positions by game, so no
+// game has positions on both sides. positions are deduplicated within each
+// side, with their results added up
+pub fn load_split<R: Rng>(
+    conn: &Connection,
+    dataset: Option<&str>,
+    test_fraction: f64,
+    options: ExportOptions,
+    rng: &mut R,
+) -> Result<(Vec<ExportRow>, Vec<ExportRow>)> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT game_id FROM game_table
+        WHERE ?1 IS NULL OR dataset = ?1
+        "#,
+    )?;
+    let mut game_ids = stmt
+        .query_map([dataset], |row| row.get::<_, i64>(0))?
+        .collect::<Result<Vec<i64>>>()?;
+    game_ids.sort_unstable();
+    game_ids.shuffle(rng);
+    let num_test = (game_ids.len() as f64 * test_fraction).round() as usize;
+    let test_games: HashSet<i64> =
+        game_ids.into_iter().take(num_test).collect();
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT state_table.game_id, state_table.state, game_table.result
+        FROM state_table
+        JOIN game_table ON state_table.game_id = game_table.game_id
+        WHERE ?1 IS NULL OR game_table.dataset = ?1
+        "#,
+    )?;
+    let rows = stmt.query_map([dataset], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get(2)?))
+    })?;
+
+    // keyed by repr string, so the output comes out in the same order
+    let mut train: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+    let mut test: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+    for row in rows {
+        let (game_id, repr, result): (i64, String, u8) = row?;
+        let Ok(state) = State::from_repr_string(&repr) else {
+            continue;
+        };
+        let split = match test_games.contains(&game_id) {
+            true => &mut test,
+            false => &mut train,
+        };
+
+        let mut reprs = vec![repr];
+        if options.mirror {
+            let mirrored = state.mirror();
+            if mirrored.board != state.board {
+                reprs.push(mirrored.to_repr_string());
+            }
+        }
+        for repr in reprs {
+            let (games, black_wins) = split.entry(repr).or_insert((0, 0));
+            *games += 1;
+            if result == 0 {
+                *black_wins += 1;
+            }
+        }
+    }
+
+    let into_rows = |split: BTreeMap<String, (u32, u32)>| {
+        split
+            .into_iter()
+            .map(|(state, (games, black_wins))| ExportRow {
+                state,
+                games,
+                black_wins,
+            })
+            .collect()
+    };
+    Ok((into_rows(train), into_rows(test)))
+}
+
+// the numbers of a row: the 37 board values (2 black, 1 white, 0 empty),
+// the heuristic scores if asked for, then the number of games and the
+// fraction black won
+fn features(
+    row: &ExportRow,
+    options: ExportOptions,
+    heuristics: &HeuristicWeights,
+) -> Vec<f64> {
+    let state = State::from_repr_string(&row.state)
+        .expect("exported states have been read before");
+    let mut values = encode(&state.board).to_vec();
+    if options.heuristics {
+        values.extend(heuristics.feature_scores(&state).map(|s| s as f64));
+    }
+    values.push(row.games as f64);
+    values.push(row.black_win_ratio());
+    values
+}
+
+fn column_names(
+    options: ExportOptions,
+    heuristics: &HeuristicWeights,
+) -> Vec<String> {
+    let mut names: Vec<String> =
+        (0..NUM_HEXES).map(|hex| format!("hex_{hex}")).collect();
+    if options.heuristics {
+        names.extend(heuristics.feature_names().map(str::to_string));
+    }
+    names.push("games".to_string());
+    names.push("black_win_ratio".to_string());
+    names
+}
+
+// a header, then one line per row, starting with the repr string
+pub fn write_csv<P: AsRef<Path>>(
+    path: P,
+    rows: &[ExportRow],
+    options: ExportOptions,
+) -> io::Result<()> {
+    let heuristics = HeuristicWeights::new([1.0; NUM_HEURISTICS]);
+    let mut file = BufWriter::new(File::create(path)?);
+
+    let mut header = vec!["state".to_string()];
+    header.extend(column_names(options, &heuristics));
+    writeln!(file, "{}", header.join(","))?;
+
+    for row in rows {
+        let values: Vec<String> = features(row, options, &heuristics)
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        writeln!(file, "{},{}", row.state, values.join(","))?;
+    }
+    file.flush()
+}
+
+// a 2d array of little-endian f64s in numpy's .npy format (version 1.0),
+// with the columns of write_csv() other than the repr string
+pub fn write_npy<P: AsRef<Path>>(
+    path: P,
+    rows: &[ExportRow],
+    options: ExportOptions,
+) -> io::Result<()> {
+    let heuristics = HeuristicWeights::new([1.0; NUM_HEURISTICS]);
+    let columns = column_names(options, &heuristics).len();
+    let mut file = BufWriter::new(File::create(path)?);
+
+    // the header is padded with spaces so the data starts on a multiple of
+    // 64 bytes, counting the magic string, version and header length
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+        rows.len(),
+        columns
+    );
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    file.write_all(b"\x93NUMPY\x01\x00")?;
+    file.write_all(&(header.len() as u16).to_le_bytes())?;
+    file.write_all(header.as_bytes())?;
+    for row in rows {
+        for value in features(row, options, &heuristics) {
+            file.write_all(&value.to_le_bytes())?;
+        }
+    }
+    file.flush()
+}
@@ -3,22 +3,13 @@
 
 #![allow(non_camel_case_types)]
 
-use std::{
-    collections::{HashMap, HashSet},
-    fmt::Debug,
-    iter::zip,
-    ops::Div,
-};
+use std::{fmt::Debug, iter::zip, ops::Div};
 
 use enum_dispatch::enum_dispatch;
-use lazy_static::lazy_static;
 
-use crate::game::{
-    board::{Hole, Move},
-    gamestate::State,
-    pieces::Piece::Black,
-    pieces::Piece::White,
-};
+use crate::game::{bitboard::Bitboard, board::Move, gamestate::State};
+
+use super::tablebase::TABLEBASE;
 
 use super::location_maps::{
     anti_centrality, black_proximity, black_proximity_row, centrality,
@@ -51,6 +42,15 @@ enum Heuristics {
 
 pub const NUM_HEURISTICS: usize = 19;
 
+const MIDDLE_HEXES: Bitboard =
+    Bitboard::from_hexes(&[0, 4, 11, 18, 25, 32, 36]);
+
+// hexes that no piece of that color can defend with a straight move
+const INVALID_BLACK_DEFENDED_PIECES: Bitboard =
+    Bitboard::from_hexes(&[0, 1, 2, 3, 5, 6, 9]);
+const INVALID_WHITE_DEFENDED_PIECES: Bitboard =
+    Bitboard::from_hexes(&[27, 30, 31, 33, 34, 35, 36]);
+
 #[enum_dispatch(Heuristics)]
 trait Heuristic {
     fn score(&self, state: &State) -> i64;
@@ -63,18 +63,8 @@ trait Heuristic {
 
 impl Heuristic for PieceDifferential {
     fn score(&self, state: &State) -> i64 {
-        let black_score = state
-            .board
-            .current_players_pieces(0)
-            .len()
-            .try_into()
-            .unwrap_or(0);
-        let white_score = state
-            .board
-            .current_players_pieces(1)
-            .len()
-            .try_into()
-            .unwrap_or(0);
+        let black_score = state.board.current_players_pieces(0).count() as i64;
+        let white_score = state.board.current_players_pieces(1).count() as i64;
 
         unsigned100_normalize(-10, 10, black_score - white_score)
     }
@@ -89,35 +79,13 @@ fn name(&self) -> &'static str {
 
 impl Heuristic for MiddleLineDifferential {
     fn score(&self, state: &State) -> i64 {
-        lazy_static! {
-            static ref MIDDLE_PIECES: HashSet<usize> = {
-                let mut middle_pieces = HashSet::new();
-                middle_pieces.insert(0);
-                middle_pieces.insert(4);
-                middle_pieces.insert(11);
-                middle_pieces.insert(18);
-                middle_pieces.insert(25);
-                middle_pieces.insert(32);
-                middle_pieces.insert(36);
-
-                middle_pieces
-            };
-        }
+        let black_score =
+            (state.board.current_players_pieces(0) & MIDDLE_HEXES).count();
 
-        let black_score: i64 = state
-            .board
-            .current_players_pieces(0)
-            .iter()
-            .map(|&elt| if MIDDLE_PIECES.contains(&elt) { 1 } else { 0 })
-            .sum();
+        let white_score =
+            (state.board.current_players_pieces(1) & MIDDLE_HEXES).count();
 
-        let white_score: i64 = state
-            .board
-            .current_players_pieces(1)
-            .iter()
-            .map(|&elt| if MIDDLE_PIECES.contains(&elt) { 1 } else { 0 })
-            .sum();
-        unsigned100_normalize(-7, 7, black_score - white_score)
+        unsigned100_normalize(-7, 7, black_score as i64 - white_score as i64)
     }
 
     fn name(&self) -> &'static str {
@@ -130,30 +98,27 @@ fn name(&self) -> &'static str {
 
 impl Heuristic for ImportantPieces {
     fn score(&self, state: &State) -> i64 {
-        let mut important_pieces_black: HashMap<usize, i64> = HashMap::new();
-        let mut important_pieces_white: HashMap<usize, i64> = HashMap::new();
-
-        important_pieces_black.insert(0, 3);
-        important_pieces_black.insert(1, 2);
-        important_pieces_black.insert(2, 2);
-
-        important_pieces_white.insert(36, 3);
-        important_pieces_white.insert(34, 2);
-        important_pieces_white.insert(35, 2);
+        // the home hex is worth 3, the two hexes in front of it are worth 2
+        fn important_pieces_value(
+            pieces: Bitboard,
+            home: usize,
+            guards: Bitboard,
+        ) -> i64 {
+            let home_value = if pieces.contains(home) { 3 } else { 0 };
+            home_value + 2 * (pieces & guards).count() as i64
+        }
 
-        let black_score: i64 = state
-            .board
-            .current_players_pieces(0)
-            .iter()
-            .map(|&elt| important_pieces_black.get(&elt).unwrap_or(&0))
-            .sum();
+        let black_score = important_pieces_value(
+            state.board.current_players_pieces(0),
+            0,
+            Bitboard::from_hexes(&[1, 2]),
+        );
 
-        let white_score: i64 = state
-            .board
-            .current_players_pieces(1)
-            .iter()
-            .map(|&elt| important_pieces_white.get(&elt).unwrap_or(&0))
-            .sum();
+        let white_score = important_pieces_value(
+            state.board.current_players_pieces(1),
+            36,
+            Bitboard::from_hexes(&[34, 35]),
+        );
 
         unsigned100_normalize(-5, 5, black_score - white_score)
     }
@@ -307,53 +272,15 @@ fn name(&self) -> &'static str {
 
 impl Heuristic for DefendedHexes {
     fn score(&self, state: &State) -> i64 {
-        let black_pieces = state.board.current_players_pieces(0);
+        let empty_hexes = !state.board.occupied();
 
-        let white_pieces = state.board.current_players_pieces(1);
+        // no two pieces share a straight hex, so counting the hexes is the
+        // same as counting the pieces that defend them
+        let black_score = (state.board.straight_hexes(0) & empty_hexes).count();
 
-        let black_straight_hexes = black_pieces
-            .iter()
-            .map(|&elt| state.board.get_straight_hex(0, elt));
-
-        let black_score = black_straight_hexes
-            .filter(|&elt| {
-                match elt {
-                    Some(i) => {
-                        // let hex_taken = black_pieces.contains(i) || white_pieces.contains(i);
-                        // !hex_taken
-                        let straight_hole = state.board.board[*i];
-                        match straight_hole {
-                            Hole(Some(_)) => false,
-                            Hole(None) => true,
-                        }
-                    }
-                    None => false,
-                }
-            })
-            .count();
+        let white_score = (state.board.straight_hexes(1) & empty_hexes).count();
 
-        let white_straight_hexes = white_pieces
-            .iter()
-            .map(|&elt| state.board.get_straight_hex(1, elt));
-
-        let white_score = white_straight_hexes
-            .filter(|&elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(_)) => false,
-                        Hole(None) => true,
-                    }
-                }
-                None => false,
-            })
-            .count();
-        unsigned100_normalize(
-            -10,
-            10,
-            i64::try_from(black_score).unwrap()
-                - i64::try_from(white_score).unwrap(),
-        )
+        unsigned100_normalize(-10, 10, black_score as i64 - white_score as i64)
     }
 
     fn name(&self) -> &'static str {
@@ -366,42 +293,16 @@ fn name(&self) -> &'static str {
 
 impl Heuristic for DefendedHexes_MiddleProximity {
     fn score(&self, state: &State) -> i64 {
-        let black_pieces = state.board.current_players_pieces(0);
-
-        let white_pieces = state.board.current_players_pieces(1);
+        let empty_hexes = !state.board.occupied();
 
-        let black_straight_hexes = black_pieces
+        let black_score: i64 = (state.board.straight_hexes(0) & empty_hexes)
             .iter()
-            .map(|&elt| state.board.get_straight_hex(0, elt));
-
-        let black_score: i64 = black_straight_hexes
-            .map(|elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(_)) => 0,
-                        Hole(None) => middle_proximity(i),
-                    }
-                }
-                None => 0,
-            })
+            .map(middle_proximity)
             .sum();
 
-        let white_straight_hexes = white_pieces
+        let white_score: i64 = (state.board.straight_hexes(1) & empty_hexes)
             .iter()
-            .map(|&elt| state.board.get_straight_hex(1, elt));
-
-        let white_score: i64 = white_straight_hexes
-            .map(|elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(_)) => 0,
-                        Hole(None) => middle_proximity(i),
-                    }
-                }
-                None => 0,
-            })
+            .map(middle_proximity)
             .sum();
 
         unsigned100_normalize(-37, 37, black_score - white_score)
@@ -417,32 +318,15 @@ fn name(&self) -> &'static str {
 
 impl Heuristic for AttackTiming {
     fn score(&self, state: &State) -> i64 {
-        lazy_static! {
-            static ref MIDDLE_PIECES: HashSet<usize> = {
-                let mut middle_pieces = HashSet::new();
-                middle_pieces.insert(0);
-                middle_pieces.insert(4);
-                middle_pieces.insert(11);
-                middle_pieces.insert(18);
-                middle_pieces.insert(25);
-                middle_pieces.insert(32);
-                middle_pieces.insert(36);
-
-                middle_pieces
-            };
-        }
-
         let black_pieces = state.board.current_players_pieces(0);
 
-        let black_most_adv_mid_val = black_pieces
+        let black_most_adv_mid_val = (black_pieces & MIDDLE_HEXES)
             .iter()
-            .filter(|hex| MIDDLE_PIECES.contains(hex))
             .map(white_proximity)
             .max();
 
-        let black_most_adv_side_val = black_pieces
+        let black_most_adv_side_val = (black_pieces & !MIDDLE_HEXES)
             .iter()
-            .filter(|hex| !MIDDLE_PIECES.contains(hex))
             .map(white_proximity)
             .max();
 
@@ -452,15 +336,13 @@ fn score(&self, state: &State) -> i64 {
 
         let white_pieces = state.board.current_players_pieces(1);
 
-        let white_most_adv_mid_val = white_pieces
+        let white_most_adv_mid_val = (white_pieces & MIDDLE_HEXES)
             .iter()
-            .filter(|hex| MIDDLE_PIECES.contains(hex))
             .map(black_proximity)
             .max();
 
-        let white_most_adv_side_val = white_pieces
+        let white_most_adv_side_val = (white_pieces & !MIDDLE_HEXES)
             .iter()
-            .filter(|hex| !MIDDLE_PIECES.contains(hex))
             .map(black_proximity)
             .max();
 
@@ -497,84 +379,25 @@ fn name(&self) -> &'static str {
 
 impl Heuristic for UndefendedPieces {
     fn score(&self, state: &State) -> i64 {
-        lazy_static! {
-            static ref INVALID_BLACK_DEFENDED_PIECES: HashSet<usize> = {
-                let mut invalid_black_defended_pieces = HashSet::new();
-                invalid_black_defended_pieces.insert(0);
-                invalid_black_defended_pieces.insert(1);
-                invalid_black_defended_pieces.insert(2);
-                invalid_black_defended_pieces.insert(3);
-                invalid_black_defended_pieces.insert(5);
-                invalid_black_defended_pieces.insert(6);
-                invalid_black_defended_pieces.insert(9);
-                invalid_black_defended_pieces
-            };
-        }
-
-        lazy_static! {
-            static ref INVALID_WHITE_DEFENDED_PIECES: HashSet<usize> = {
-                let mut invalid_white_defended_pieces = HashSet::new();
-                invalid_white_defended_pieces.insert(27);
-                invalid_white_defended_pieces.insert(30);
-                invalid_white_defended_pieces.insert(31);
-                invalid_white_defended_pieces.insert(33);
-                invalid_white_defended_pieces.insert(34);
-                invalid_white_defended_pieces.insert(35);
-                invalid_white_defended_pieces.insert(36);
-                invalid_white_defended_pieces
-            };
-        }
-
         let black_pieces = state.board.current_players_pieces(0);
 
-        let num_valid_black_pieces = black_pieces
-            .iter()
-            .filter(|&elt| !INVALID_BLACK_DEFENDED_PIECES.contains(elt))
-            .count();
+        let num_valid_black_pieces =
+            (black_pieces & !INVALID_BLACK_DEFENDED_PIECES).count();
 
         let white_pieces = state.board.current_players_pieces(1);
 
-        let num_valid_white_pieces = white_pieces
-            .iter()
-            .filter(|&elt| !INVALID_WHITE_DEFENDED_PIECES.contains(elt))
-            .count();
+        let num_valid_white_pieces =
+            (white_pieces & !INVALID_WHITE_DEFENDED_PIECES).count();
 
-        let black_straight_hexes = black_pieces
-            .iter()
-            .map(|&elt| state.board.get_straight_hex(0, elt));
-
-        let black_defended_pieces = black_straight_hexes
-            .filter(|&elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(Black)) => true,
-                        Hole(_) => false,
-                    }
-                }
-                None => false,
-            })
-            .count();
+        // a defended piece always sits on a valid hex
+        let black_defended_pieces =
+            (state.board.straight_hexes(0) & black_pieces).count();
 
         let black_undefended_pieces =
             num_valid_black_pieces - black_defended_pieces;
 
-        let white_straight_hexes = white_pieces
-            .iter()
-            .map(|&elt| state.board.get_straight_hex(1, elt));
-
-        let white_defended_pieces = white_straight_hexes
-            .filter(|&elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(White)) => true,
-                        Hole(_) => false,
-                    }
-                }
-                None => false,
-            })
-            .count();
+        let white_defended_pieces =
+            (state.board.straight_hexes(1) & white_pieces).count();
 
         let white_undefended_pieces =
             num_valid_white_pieces - white_defended_pieces;
@@ -582,8 +405,7 @@ fn score(&self, state: &State) -> i64 {
         unsigned100_normalize(
             -10,
             10,
-            i64::try_from(white_undefended_pieces).unwrap()
-                - i64::try_from(black_undefended_pieces).unwrap(),
+            white_undefended_pieces as i64 - black_undefended_pieces as i64,
         )
     }
 
@@ -597,99 +419,24 @@ fn name(&self) -> &'static str {
 
 impl Heuristic for UndefendedPieces_MiddleProximity {
     fn score(&self, state: &State) -> i64 {
-        lazy_static! {
-            static ref INVALID_BLACK_DEFENDED_PIECES: HashSet<usize> = {
-                let mut invalid_black_defended_pieces = HashSet::new();
-                invalid_black_defended_pieces.insert(0);
-                invalid_black_defended_pieces.insert(1);
-                invalid_black_defended_pieces.insert(2);
-                invalid_black_defended_pieces.insert(3);
-                invalid_black_defended_pieces.insert(5);
-                invalid_black_defended_pieces.insert(6);
-                invalid_black_defended_pieces.insert(9);
-                invalid_black_defended_pieces
-            };
-        }
-
-        lazy_static! {
-            static ref INVALID_WHITE_DEFENDED_PIECES: HashSet<usize> = {
-                let mut invalid_white_defended_pieces = HashSet::new();
-                invalid_white_defended_pieces.insert(27);
-                invalid_white_defended_pieces.insert(30);
-                invalid_white_defended_pieces.insert(31);
-                invalid_white_defended_pieces.insert(33);
-                invalid_white_defended_pieces.insert(34);
-                invalid_white_defended_pieces.insert(35);
-                invalid_white_defended_pieces.insert(36);
-                invalid_white_defended_pieces
-            };
-        }
-
         let black_pieces = state.board.current_players_pieces(0);
 
         let white_pieces = state.board.current_players_pieces(1);
 
-        let black_straight_hexes = black_pieces
-            .iter()
-            .map(|&elt| state.board.get_straight_hex(0, elt));
-
-        let black_defended_pieces: HashSet<&usize> = black_straight_hexes
-            .filter(|&elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(Black)) => true,
-                        Hole(_) => false,
-                    }
-                }
-                None => false,
-            })
-            .map(|elt| elt.unwrap())
-            .collect();
+        let black_undefended_pieces = black_pieces
+            & !INVALID_BLACK_DEFENDED_PIECES
+            & !state.board.straight_hexes(0);
 
-        let value_black_undefended_pieces: i64 = black_pieces
-            .iter()
-            .map(|&elt| {
-                if INVALID_BLACK_DEFENDED_PIECES.contains(&elt)
-                    || black_defended_pieces.contains(&elt)
-                {
-                    0
-                } else {
-                    middle_proximity(&elt)
-                }
-            })
-            .sum();
+        let value_black_undefended_pieces: i64 =
+            black_undefended_pieces.iter().map(middle_proximity).sum();
 
-        let white_straight_hexes = white_pieces
-            .iter()
-            .map(|&elt| state.board.get_straight_hex(1, elt));
-
-        let white_defended_pieces: HashSet<&usize> = white_straight_hexes
-            .filter(|&elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(White)) => true,
-                        Hole(_) => false,
-                    }
-                }
-                None => false,
-            })
-            .map(|elt| elt.unwrap())
-            .collect();
+        let white_undefended_pieces = white_pieces
+            & !INVALID_WHITE_DEFENDED_PIECES
+            & !state.board.straight_hexes(1);
+
+        let value_white_undefended_pieces: i64 =
+            white_undefended_pieces.iter().map(middle_proximity).sum();
 
-        let value_white_undefended_pieces: i64 = white_pieces
-            .iter()
-            .map(|&elt| {
-                if INVALID_WHITE_DEFENDED_PIECES.contains(&elt)
-                    || white_defended_pieces.contains(&elt)
-                {
-                    0
-                } else {
-                    middle_proximity(&elt)
-                }
-            })
-            .sum();
         unsigned100_normalize(
             -37,
             37,
@@ -711,39 +458,13 @@ fn score(&self, state: &State) -> i64 {
 
         let white_pieces = state.board.current_players_pieces(1);
 
-        let black_straight_hexes = black_pieces
-            .iter()
-            .map(|&elt| state.board.get_straight_hex(0, elt));
-
-        let black_score = black_straight_hexes
-            .filter(|&elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    matches!(straight_hole, Hole(Some(Black)))
-                }
-                None => false,
-            })
-            .count();
+        let black_score =
+            (state.board.straight_hexes(0) & black_pieces).count();
 
-        let white_straight_hexes = white_pieces
-            .iter()
-            .map(|&elt| state.board.get_straight_hex(1, elt));
-
-        let white_score = white_straight_hexes
-            .filter(|&elt| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    matches!(straight_hole, Hole(Some(White)))
-                }
-                None => false,
-            })
-            .count();
-        unsigned100_normalize(
-            -8,
-            8,
-            i64::try_from(black_score).unwrap()
-                - i64::try_from(white_score).unwrap(),
-        )
+        let white_score =
+            (state.board.straight_hexes(1) & white_pieces).count();
+
+        unsigned100_normalize(-8, 8, black_score as i64 - white_score as i64)
         // potentially use an iterative DFS to avoid double counting?
     }
 
@@ -761,39 +482,24 @@ fn score(&self, state: &State) -> i64 {
 
         let white_pieces = state.board.current_players_pieces(1);
 
-        let black_straight_hexes = black_pieces
+        let black_score: i64 = black_pieces
             .iter()
-            .map(|&elt| (elt, state.board.get_straight_hex(0, elt)));
-
-        let black_score: i64 = black_straight_hexes
-            .map(|(idx, elt)| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(Black)) => middle_proximity(&idx),
-                        _ => 0,
-                    }
-                }
-                None => 0,
+            .filter(|&idx| match state.board.get_straight_hex(0, idx) {
+                Some(i) => black_pieces.contains(i),
+                None => false,
             })
+            .map(middle_proximity)
             .sum();
 
-        let white_straight_hexes = white_pieces
+        let white_score: i64 = white_pieces
             .iter()
-            .map(|&elt| (elt, state.board.get_straight_hex(1, elt)));
-
-        let white_score: i64 = white_straight_hexes
-            .map(|(idx, elt)| match elt {
-                Some(i) => {
-                    let straight_hole = state.board.board[*i];
-                    match straight_hole {
-                        Hole(Some(White)) => middle_proximity(&idx),
-                        _ => 0,
-                    }
-                }
-                None => 0,
+            .filter(|&idx| match state.board.get_straight_hex(1, idx) {
+                Some(i) => white_pieces.contains(i),
+                None => false,
             })
+            .map(middle_proximity)
             .sum();
+
         unsigned100_normalize(-42, 42, black_score - white_score)
     }
 
@@ -812,10 +518,10 @@ fn score(&self, state: &State) -> i64 {
         let white_pieces = state.board.current_players_pieces(1);
 
         let black_furthest =
-            black_proximity_row(black_pieces.last().unwrap_or(&0));
+            black_proximity_row(black_pieces.last().unwrap_or(0));
 
         let white_furthest =
-            white_proximity_row(white_pieces.first().unwrap_or(&36));
+            white_proximity_row(white_pieces.first().unwrap_or(36));
 
         let black_score = black_pieces
             .iter()
@@ -827,8 +533,7 @@ fn score(&self, state: &State) -> i64 {
             .filter(|&elt| white_proximity_row(elt) >= 12 - black_furthest)
             .count();
 
-        let diff = i64::try_from(white_score).unwrap()
-            - i64::try_from(black_score).unwrap();
+        let diff = white_score as i64 - black_score as i64;
 
         if diff <= -2 {
             -1000
@@ -854,10 +559,10 @@ fn score(&self, state: &State) -> i64 {
         let white_pieces = state.board.current_players_pieces(1);
 
         let black_furthest =
-            black_proximity_row(black_pieces.last().unwrap_or(&0));
+            black_proximity_row(black_pieces.last().unwrap_or(0));
 
         let white_furthest =
-            white_proximity_row(white_pieces.first().unwrap_or(&36));
+            white_proximity_row(white_pieces.first().unwrap_or(36));
 
         let black_aggr_pieces = black_pieces
             .iter()
@@ -873,8 +578,8 @@ fn score(&self, state: &State) -> i64 {
         let white_score: i64 =
             white_aggr_pieces.clone().map(middle_proximity).sum();
 
-        let diff = i64::try_from(white_aggr_pieces.count()).unwrap()
-            - i64::try_from(black_aggr_pieces.count()).unwrap();
+        let diff =
+            white_aggr_pieces.count() as i64 - black_aggr_pieces.count() as i64;
 
         if diff <= -2 {
             -1000
@@ -900,10 +605,10 @@ fn score(&self, state: &State) -> i64 {
         let white_pieces = state.board.current_players_pieces(1);
 
         let black_furthest =
-            black_proximity_row(black_pieces.last().unwrap_or(&0));
+            black_proximity_row(black_pieces.last().unwrap_or(0));
 
         let white_furthest =
-            white_proximity_row(white_pieces.first().unwrap_or(&36));
+            white_proximity_row(white_pieces.first().unwrap_or(36));
 
         let black_aggr_pieces = black_pieces
             .iter()
@@ -919,8 +624,8 @@ fn score(&self, state: &State) -> i64 {
         let white_score: i64 =
             white_aggr_pieces.clone().map(anti_centrality).sum();
 
-        let diff = i64::try_from(white_aggr_pieces.count()).unwrap()
-            - i64::try_from(black_aggr_pieces.count()).unwrap();
+        let diff =
+            white_aggr_pieces.count() as i64 - black_aggr_pieces.count() as i64;
 
         if diff <= -2 {
             -1000
@@ -1025,7 +730,16 @@ pub fn new_with_state(&self, state: &State) -> HeuristicWeightsWithState {
         }
     }
 
+    // score() is the exact score from the tablebase when it covers the
+    // position, and the heuristics' otherwise
     pub fn score(&self, state: &State) -> f64 {
+        match TABLEBASE.as_ref().and_then(|tb| tb.score(state)) {
+            Some(score) => score,
+            None => self.heuristic_score(state),
+        }
+    }
+
+    pub fn heuristic_score(&self, state: &State) -> f64 {
         if !state.active {
             match state.winner {
                 Some(0) => return f64::MAX,
@@ -1041,6 +755,23 @@ pub fn score(&self, state: &State) -> f64 {
         result
     }
 
+    // the score of each heuristic on its own, before weighting
+    pub fn feature_scores(&self, state: &State) -> [i64; NUM_HEURISTICS] {
+        let mut scores = [0; NUM_HEURISTICS];
+        for (score, heuristic_fn) in zip(&mut scores, self.functions.iter()) {
+            *score = heuristic_fn.score(state);
+        }
+        scores
+    }
+
+    pub fn feature_names(&self) -> [&'static str; NUM_HEURISTICS] {
+        let mut names = [""; NUM_HEURISTICS];
+        for (name, heuristic_fn) in zip(&mut names, self.functions.iter()) {
+            *name = heuristic_fn.name();
+        }
+        names
+    }
+
     pub fn new_with_state_and_move(
         &self,
         mut state: State,
@@ -0,0 +1,297 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+use crate::game::board::Move::{self, Diagonal, Straight};
+use crate::game::gamestate::State;
+
+use super::heuristics::{HeuristicWeights, Weights};
+use super::tree::SearchLimit;
+
+/// how games are played out from a new node of the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rollout {
+    // uniformly random moves
+    Random,
+    // winning moves when there are any, otherwise the move the heuristics
+    // like best for the player moving (with some random moves mixed in)
+    Heuristic,
+}
+
+/// the usual UCT exploration constant, for win rates between 0 and 1
+pub const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+// how often a heuristic rollout plays a random move instead of the best one
+const ROLLOUT_EPSILON: f64 = 0.1;
+
+// how many iterations are run between checks of the clock
+const ITERATIONS_PER_TIME_CHECK: u32 = 64;
+
+struct Node {
+    // the move that led here from the parent (None at the root)
+    mv: Option<Move>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    // the player who made `mv`, whose wins are counted here
+    player: u8,
+    visits: u32,
+    wins: f64,
+}
+
+impl Node {
+    fn new(
+        mv: Option<Move>,
+        parent: Option<usize>,
+        state: &State,
+        player: u8,
+    ) -> Node {
+        Node {
+            mv,
+            parent,
+            children: vec![],
+            untried: state.current_possible_moves(state.current_turn).to_vec(),
+            player,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+
+    // upper confidence bound of this node, as seen from its parent
+    fn uct(&self, parent_visits: u32, exploration: f64) -> f64 {
+        let visits = self.visits as f64;
+        self.wins / visits
+            + exploration * ((parent_visits as f64).ln() / visits).sqrt()
+    }
+}
+
+/// monte carlo tree search using UCT. every iteration walks down the tree
+/// to a node that isn't fully expanded, adds one child, plays a game out
+/// from there and counts the result in every node on the way back up
+pub struct Mcts {
+    weights: HeuristicWeights,
+    rollout: Rollout,
+    exploration: f64,
+    nodes: Vec<Node>,
+}
+
+impl Mcts {
+    pub fn new(weights: &Weights, rollout: Rollout, exploration: f64) -> Mcts {
+        Mcts {
+            weights: HeuristicWeights::new(weights.to_owned()),
+            rollout,
+            exploration,
+            nodes: vec![],
+        }
+    }
+
+    // search() runs playouts until the time limit, or up to a number of
+    // iterations. a depth limit doesn't mean anything to MCTS
+    pub fn search<R: Rng>(
+        &mut self,
+        state: &State,
+        limit: &SearchLimit,
+        rng: &mut R,
+    ) -> MctsSuggestion {
+        let beginning = Instant::now();
+        let (time_limit, max_iterations) = match limit {
+            SearchLimit::Time(time_limit) => (Some(*time_limit), u32::MAX),
+            SearchLimit::Depth(_) => {
+                panic!("MCTS takes a time or iterations limit, not {limit}")
+            }
+            SearchLimit::Iterations(iterations) => (None, *iterations),
+        };
+
+        self.nodes = vec![Node::new(None, None, state, 1 - state.current_turn)];
+        let mut iterations = 0;
+        while iterations < max_iterations.max(1) {
+            self.iterate(state.clone(), rng);
+            iterations += 1;
+
+            if iterations % ITERATIONS_PER_TIME_CHECK == 0
+                && time_limit.is_some_and(|t| beginning.elapsed() >= t)
+            {
+                break;
+            }
+        }
+
+        let mut root_moves: Vec<RootMoveStats> = self.nodes[0]
+            .children
+            .iter()
+            .map(|&child| {
+                let node = &self.nodes[child];
+                RootMoveStats {
+                    mv: node.mv.expect("only the root has no move"),
+                    visits: node.visits,
+                    win_rate: node.wins / node.visits as f64,
+                }
+            })
+            .collect();
+        root_moves.sort_by(|a, b| {
+            b.visits
+                .cmp(&a.visits)
+                .then(b.win_rate.total_cmp(&a.win_rate))
+        });
+
+        MctsSuggestion {
+            suggestion: root_moves
+                .first()
+                .expect("MCTS did not expand a single move")
+                .mv,
+            iterations,
+            time_searching: beginning.elapsed(),
+            tree_size: self.nodes.len(),
+            root_moves,
+        }
+    }
+
+    fn iterate<R: Rng>(&mut self, mut state: State, rng: &mut R) {
+        // selection
+        let mut current = 0;
+        while self.nodes[current].untried.is_empty()
+            && !self.nodes[current].children.is_empty()
+        {
+            let parent_visits = self.nodes[current].visits;
+            current = *self.nodes[current]
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    let a = self.nodes[a].uct(parent_visits, self.exploration);
+                    let b = self.nodes[b].uct(parent_visits, self.exploration);
+                    a.total_cmp(&b)
+                })
+                .expect("a fully expanded node has children");
+            state.make_move(self.nodes[current].mv.unwrap());
+        }
+
+        // expansion
+        let untried = &mut self.nodes[current].untried;
+        if !untried.is_empty() {
+            let m = untried.swap_remove(rng.gen_range(0..untried.len()));
+            let player = state.current_turn;
+            state.make_move(m);
+
+            let child = self.nodes.len();
+            self.nodes
+                .push(Node::new(Some(m), Some(current), &state, player));
+            self.nodes[current].children.push(child);
+            current = child;
+        }
+
+        // simulation
+        let winner = self.play_out(&mut state, rng);
+
+        // backpropagation
+        let mut node = Some(current);
+        while let Some(idx) = node {
+            let n = &mut self.nodes[idx];
+            n.visits += 1;
+            if Some(n.player) == winner {
+                n.wins += 1.0;
+            }
+            node = n.parent;
+        }
+    }
+
+    // plays until the game is over, returning the winner. pieces only move
+    // forward, so every game ends
+    fn play_out<R: Rng>(&self, state: &mut State, rng: &mut R) -> Option<u8> {
+        while state.active {
+            let moves = state.current_possible_moves(state.current_turn);
+            let m = match self.rollout {
+                Rollout::Random => moves.choose(rng).copied(),
+                Rollout::Heuristic => self.heuristic_move(state, &moves, rng),
+            };
+            match m {
+                Some(m) => state.make_move(m),
+                None => break,
+            };
+        }
+        state.winner
+    }
+
+    fn heuristic_move<R: Rng>(
+        &self,
+        state: &mut State,
+        moves: &[Move],
+        rng: &mut R,
+    ) -> Option<Move> {
+        if let Some(&m) = moves.iter().find(|&&m| {
+            let (Diagonal(_, to) | Straight(_, to)) = m;
+            to == 0 || to == 36
+        }) {
+            return Some(m);
+        }
+        if rng.gen_bool(ROLLOUT_EPSILON) {
+            return moves.choose(rng).copied();
+        }
+
+        // black maximizes the score, white minimizes it
+        let sign = match state.current_turn {
+            0 => 1.0,
+            _ => -1.0,
+        };
+        moves
+            .iter()
+            .map(|&m| {
+                let undo = state.make_move(m);
+                let score = sign * self.weights.score(state);
+                state.unmake_move(undo);
+                (m, score)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(m, _)| m)
+    }
+}
+
+/// how a move at the root fared in the search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RootMoveStats {
+    pub mv: Move,
+    pub visits: u32,
+    // the fraction of playouts through this move won by the player to move
+    pub win_rate: f64,
+}
+
+pub struct MctsSuggestion {
+    pub suggestion: Move,
+    iterations: u32,
+    time_searching: Duration,
+    tree_size: usize,
+    // sorted with the most visited move first
+    root_moves: Vec<RootMoveStats>,
+}
+
+impl MctsSuggestion {
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    pub fn root_moves(&self) -> &[RootMoveStats] {
+        &self.root_moves
+    }
+}
+
+impl Debug for MctsSuggestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "MCTS suggested {:?} ({} iterations, {} nodes) in {:.2} seconds. Root moves:",
+            self.suggestion,
+            self.iterations,
+            self.tree_size,
+            self.time_searching.as_secs_f32(),
+        ))?;
+        for stats in &self.root_moves {
+            f.write_fmt(format_args!(
+                "\n\t{:?}: {} visits, {:.1}% won",
+                stats.mv,
+                stats.visits,
+                100.0 * stats.win_rate
+            ))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use crate::game::bitboard::NUM_HEXES;
+use crate::game::board::Board;
+use crate::game::gamestate::State;
+
+/// the input the networks are trained on: one value per hex, 2 for a black
+/// piece, 1 for a white piece and 0 for an empty hex (as in
+/// `neuralnet/predict.py`)
+pub fn encode(board: &Board) -> [f64; NUM_HEXES] {
+    let mut input = [0.0; NUM_HEXES];
+    for hex in board.current_players_pieces(0) {
+        input[hex] = 2.0;
+    }
+    for hex in board.current_players_pieces(1) {
+        input[hex] = 1.0;
+    }
+    input
+}
+
+/// the activation functions of sklearn's `MLPRegressor`, by the same names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Activation {
+    Identity,
+    Logistic,
+    Tanh,
+    Relu,
+}
+
+impl Activation {
+    pub(crate) fn apply(self, x: f64) -> f64 {
+        match self {
+            Activation::Identity => x,
+            Activation::Logistic => 1.0 / (1.0 + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::Relu => x.max(0.0),
+        }
+    }
+
+    // the derivative at the input that gave `output`
+    pub(crate) fn derivative(self, output: f64) -> f64 {
+        match self {
+            Activation::Identity => 1.0,
+            Activation::Logistic => output * (1.0 - output),
+            Activation::Tanh => 1.0 - output * output,
+            Activation::Relu => match output > 0.0 {
+                true => 1.0,
+                false => 0.0,
+            },
+        }
+    }
+}
+
+/// a multi-layer perceptron with a single output, evaluated without python.
+/// models are stored as JSON with the attributes of a fitted sklearn
+/// `MLPRegressor` (`neuralnet/export.py` writes them):
+///
+/// ```json
+/// {
+///   "activation": "relu",
+///   "out_activation": "identity",
+///   "coefs": [[[...], ...], ...],
+///   "intercepts": [[...], ...]
+/// }
+/// ```
+///
+/// `coefs[i][j][k]` is the weight from unit `j` of layer `i` to unit `k` of
+/// layer `i + 1` (sklearn's `coefs_`), and `intercepts[i]` are the biases of
+/// layer `i + 1` (`intercepts_`). the first layer has 37 inputs, encoded by
+/// `encode()`, and the last has one output: the chance that black wins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mlp {
+    pub activation: Activation,
+    pub out_activation: Activation,
+    pub coefs: Vec<Vec<Vec<f64>>>,
+    pub intercepts: Vec<Vec<f64>>,
+}
+
+impl Mlp {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Mlp> {
+        let file = BufReader::new(File::open(path)?);
+        let mlp: Mlp = serde_json::from_reader(file)?;
+        mlp.check_shape()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(mlp)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, self)?;
+        Ok(())
+    }
+
+    // check_shape() makes sure every layer lines up with the next
+    fn check_shape(&self) -> Result<(), String> {
+        if self.coefs.is_empty() || self.coefs.len() != self.intercepts.len() {
+            return Err("there should be one set of intercepts per layer of \
+                        coefs"
+                .to_string());
+        }
+
+        let mut inputs = NUM_HEXES;
+        for (i, (layer, biases)) in
+            self.coefs.iter().zip(&self.intercepts).enumerate()
+        {
+            let outputs = biases.len();
+            if layer.len() != inputs
+                || layer.iter().any(|weights| weights.len() != outputs)
+            {
+                return Err(format!(
+                    "layer {i} should be {inputs} by {outputs}"
+                ));
+            }
+            inputs = outputs;
+        }
+
+        match inputs {
+            1 => Ok(()),
+            _ => Err(format!("the model has {inputs} outputs instead of 1")),
+        }
+    }
+
+    pub fn predict(&self, input: &[f64; NUM_HEXES]) -> f64 {
+        self.forward(input).last().expect("a model has layers")[0]
+    }
+
+    // forward() gives the output of every layer, starting with the input
+    pub(crate) fn forward(&self, input: &[f64]) -> Vec<Vec<f64>> {
+        let mut outputs = vec![input.to_vec()];
+        let last = self.coefs.len() - 1;
+        for (i, (layer, biases)) in
+            self.coefs.iter().zip(&self.intercepts).enumerate()
+        {
+            let activation = match i {
+                i if i == last => self.out_activation,
+                _ => self.activation,
+            };
+            let mut next = biases.clone();
+            for (value, weights) in outputs[i].iter().zip(layer) {
+                if *value == 0.0 {
+                    continue;
+                }
+                for (n, w) in next.iter_mut().zip(weights) {
+                    *n += value * w;
+                }
+            }
+            for n in next.iter_mut() {
+                *n = activation.apply(*n);
+            }
+            outputs.push(next);
+        }
+        outputs
+    }
+
+    // the chance that black wins from `state`, as the network sees it
+    pub fn evaluate(&self, state: &State) -> f64 {
+        self.predict(&encode(&state.board))
+    }
+}
@@ -1,3 +1,13 @@
+pub mod book;
+pub mod evaluator;
+pub mod export;
 pub mod heuristics;
 mod location_maps;
+pub mod mcts;
+pub mod mlp;
+pub mod nn;
+pub mod search;
+pub mod tablebase;
+pub mod training;
+pub mod transposition;
 pub mod tree;
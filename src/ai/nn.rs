@@ -0,0 +1,157 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyString, PyTuple};
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use crate::game::board::Board;
+use crate::game::gamestate::State;
+
+use super::mlp::{encode, Mlp};
+
+/// a trained network, run either natively or through `neuralnet/predict.py`
+#[derive(Clone, PartialEq)]
+pub enum NnModel {
+    // a joblib file
+    Python(String),
+    // a JSON export (see `Mlp`)
+    Native(Arc<Mlp>),
+}
+
+impl Default for NnModel {
+    fn default() -> Self {
+        NnModel::Python(String::default())
+    }
+}
+
+impl fmt::Debug for NnModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NnModel::Python(path) => write!(f, "Python({path:?})"),
+            NnModel::Native(mlp) => {
+                write!(f, "Native({} layers)", mlp.coefs.len())
+            }
+        }
+    }
+}
+
+/// what the network sees of a position, without the rest of its `State`
+/// (histories and players), so the positions a move leads to can be batched
+/// up cheaply
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snapshot {
+    pub board: Board,
+    pub current_turn: u8,
+    pub winner: Option<u8>,
+}
+
+impl Snapshot {
+    pub fn of(state: &State) -> Snapshot {
+        Snapshot {
+            board: state.board,
+            current_turn: state.current_turn,
+            winner: state.winner,
+        }
+    }
+
+    // the position as State::to_repr_string gives it (for a game that isn't
+    // over)
+    fn to_repr_string(self) -> String {
+        match self.current_turn {
+            0 => "b:".to_owned() + &self.board.to_repr_string(),
+            _ => "w:".to_owned() + &self.board.to_repr_string(),
+        }
+    }
+}
+
+impl NnModel {
+    // models exported to JSON are run natively, anything else is loaded
+    // with joblib through python
+    pub fn load(path: &str) -> io::Result<NnModel> {
+        match path.ends_with(".json") {
+            true => Ok(NnModel::Native(Arc::new(Mlp::load(path)?))),
+            false => Ok(NnModel::Python(path.to_string())),
+        }
+    }
+
+    // the chance that black wins from each of `states`, as the network sees
+    // it (see evaluate_snapshots)
+    pub fn evaluate_batch(&self, states: &[State]) -> Vec<f64> {
+        let snapshots: Vec<Snapshot> =
+            states.iter().map(Snapshot::of).collect();
+        self.evaluate_snapshots(&snapshots)
+    }
+
+    // the chance that black wins from each of `snapshots`. the model is
+    // loaded (and python entered) once for the whole batch, and finished
+    // games are scored by their winner
+    pub fn evaluate_snapshots(&self, snapshots: &[Snapshot]) -> Vec<f64> {
+        let scores: Vec<Option<f64>> = snapshots
+            .iter()
+            .map(|snapshot| match snapshot.winner {
+                Some(0) => Some(1.0),
+                Some(_) => Some(0.0),
+                None => None,
+            })
+            .collect();
+        let unfinished: Vec<Snapshot> = snapshots
+            .iter()
+            .filter(|snapshot| snapshot.winner.is_none())
+            .copied()
+            .collect();
+
+        let predictions = match self {
+            NnModel::Native(mlp) => unfinished
+                .iter()
+                .map(|snapshot| mlp.predict(&encode(&snapshot.board)))
+                .collect(),
+            NnModel::Python(path) => {
+                let reprs: Vec<String> = unfinished
+                    .iter()
+                    .map(|snapshot| snapshot.to_repr_string())
+                    .collect();
+                run_python_nn(&reprs, path).unwrap()
+            }
+        };
+
+        let mut predictions = predictions.into_iter();
+        scores
+            .into_iter()
+            .map(|score| {
+                score.unwrap_or_else(|| {
+                    predictions.next().expect("a prediction per state")
+                })
+            })
+            .collect()
+    }
+
+    pub fn evaluate(&self, state: &State) -> f64 {
+        self.evaluate_batch(std::slice::from_ref(state))[0]
+    }
+}
+
+fn run_python_nn(state_reprs: &[String], path: &str) -> PyResult<Vec<f64>> {
+    if state_reprs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let code_location = "neuralnet/predict.py";
+    let code = std::fs::read_to_string(code_location)?;
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| -> PyResult<Vec<f64>> {
+        let module =
+            PyModule::from_code(py, &code, "predict.py", "predict.py")?;
+
+        let nn_function = module.getattr("run_nn_batch")?;
+        let args = PyTuple::new(
+            py,
+            [
+                PyList::new(py, state_reprs).to_object(py),
+                PyString::new(py, path).to_object(py),
+            ],
+        );
+        nn_function.call(args, None)?.extract::<Vec<f64>>()
+    })
+}
@@ -0,0 +1,814 @@
+use lazy_static::lazy_static;
+use log::info;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use separator::Separatable;
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Debug;
+use std::iter;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::game::bitboard::NUM_HEXES;
+use crate::game::board::{
+    get_moves_of_piece,
+    Move::{self, Diagonal, Straight},
+};
+use crate::game::gamestate::State;
+
+use super::evaluator::Evaluator;
+use super::heuristics::{HeuristicWeights, Weights};
+use super::tablebase::TABLEBASE;
+use super::transposition::{Bound, Entry, TranspositionTable};
+use super::tree::SearchLimit;
+
+/// the score of a won game. wins closer to the root score higher, so the
+/// searcher goes for the quickest win (and the slowest loss)
+pub const WIN_SCORE: f64 = 1e15;
+
+// deeper than any game of milestone can last
+const MAX_PLY: usize = 128;
+
+// how many nodes are searched between checks of the clock
+const NODES_PER_TIME_CHECK: usize = 1024;
+
+// moves are searched by category, highest first. quiet moves are then ordered
+// by their history score
+const WIN_ORDER: u8 = 5;
+const CAPTURE_ORDER: u8 = 4;
+const PV_ORDER: u8 = 3;
+const HASH_ORDER: u8 = 2;
+const KILLER_ORDER: u8 = 1;
+const THREAT_ORDER: u8 = 1;
+const QUIET_ORDER: u8 = 0;
+
+lazy_static! {
+    static ref SEARCH_THREADS: usize =
+        env::var("SEARCH_THREADS").map_or(1, |elt| match elt.parse() {
+            Ok(i) => {
+                info!("Using SEARCH_THREADS environment variable ({})", i);
+                i
+            }
+            _ => 1,
+        });
+    // the pools parallel searches run in, by number of threads, which are
+    // shared by every search rather than started for each move
+    static ref THREAD_POOLS: Mutex<HashMap<usize, Arc<ThreadPool>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// options for how the searcher explores the tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchConfig {
+    // search the moves most likely to cause a cutoff first (wins, captures,
+    // the last principal variation, killer moves, then by history). doesn't
+    // change the score of a fixed-depth search, only how long it takes
+    pub move_ordering: bool,
+    // how many plies past the search depth to keep following captures and
+    // threats to move home, so leaves aren't evaluated mid-exchange.
+    // 0 turns the quiescence search off
+    pub quiescence_depth: u8,
+    // how many threads search each move. with more than one, the moves at
+    // the root are split between them, and results can vary from run to run
+    pub threads: usize,
+    // look positions up in the tablebase at TABLEBASE_PATH, if there is one
+    pub tablebase: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            move_ordering: true,
+            quiescence_depth: 4,
+            threads: *SEARCH_THREADS,
+            tablebase: true,
+        }
+    }
+}
+
+/// depth-first negamax search with alpha-beta pruning. unlike `GameTree`,
+/// the only positions kept around are the ones on the current line, which
+/// are made and unmade in place
+pub struct Searcher {
+    // these two are shared with the helpers of a parallel search
+    evaluator: Arc<dyn Evaluator>,
+    table: Arc<TranspositionTable>,
+    nodes: usize,
+    deadline: Option<Instant>,
+    // how many nodes each thread may search
+    node_limit: Option<usize>,
+    stop: Arc<AtomicBool>,
+    // pv[ply] is the best line found from the position at `ply`
+    pv: Vec<Vec<Move>>,
+    // the best line found by the previous iteration
+    previous_pv: Vec<Move>,
+    // quiet moves that recently caused a cutoff at each ply
+    killers: Vec<[Option<Move>; 2]>,
+    // how much each quiet move (by player, origin and destination) has
+    // caused cutoffs, weighted towards cutoffs far from the leaves
+    history: Box<[[[u32; NUM_HEXES]; NUM_HEXES]; 2]>,
+    config: SearchConfig,
+}
+
+impl Searcher {
+    pub fn new(weights: &Weights) -> Searcher {
+        Searcher::with_config(weights, SearchConfig::default())
+    }
+
+    pub fn with_config(weights: &Weights, config: SearchConfig) -> Searcher {
+        Searcher::with_evaluator(
+            Arc::new(HeuristicWeights::new(weights.to_owned())),
+            config,
+        )
+    }
+
+    // a searcher that scores its leaves with something other than the
+    // heuristics, such as a trained network
+    pub fn with_evaluator(
+        evaluator: Arc<dyn Evaluator>,
+        config: SearchConfig,
+    ) -> Searcher {
+        Searcher {
+            evaluator,
+            table: Arc::new(TranspositionTable::default()),
+            nodes: 0,
+            deadline: None,
+            node_limit: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            pv: vec![vec![]; MAX_PLY + 1],
+            previous_pv: vec![],
+            killers: vec![[None; 2]; MAX_PLY + 1],
+            history: Box::new([[[0; NUM_HEXES]; NUM_HEXES]; 2]),
+            config,
+        }
+    }
+
+    // a searcher for another thread, sharing this one's table and clock
+    fn helper(&self) -> Searcher {
+        Searcher {
+            evaluator: Arc::clone(&self.evaluator),
+            table: Arc::clone(&self.table),
+            nodes: 0,
+            deadline: self.deadline,
+            node_limit: self.node_limit,
+            stop: Arc::clone(&self.stop),
+            pv: vec![vec![]; MAX_PLY + 1],
+            previous_pv: vec![],
+            killers: vec![[None; 2]; MAX_PLY + 1],
+            history: Box::new([[[0; NUM_HEXES]; NUM_HEXES]; 2]),
+            config: self.config,
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    // search() iteratively deepens until the limit is reached, and returns
+    // the result of the deepest search that finished
+    pub fn search(
+        &mut self,
+        state: &State,
+        limit: &SearchLimit,
+    ) -> SuggestedMove {
+        let beginning = Instant::now();
+        // a move needs at least one ply searched
+        let max_depth = match limit {
+            SearchLimit::Depth(depth) => (*depth).max(1),
+            SearchLimit::Time(_) | SearchLimit::Iterations(_) => 100,
+        };
+
+        self.deadline = None;
+        self.node_limit = None;
+        self.stop.store(false, Ordering::Relaxed);
+
+        let mut state = state.clone();
+        let mut depth_searched = 0;
+        let mut best_line: Vec<Move> = vec![];
+
+        let mut parallel = match self.config.threads {
+            0 | 1 => None,
+            threads => {
+                let pool = thread_pool(threads);
+                let helpers: Vec<Searcher> =
+                    (1..threads).map(|_| self.helper()).collect();
+                Some((pool, helpers))
+            }
+        };
+
+        for depth in 1..=max_depth {
+            self.table.new_search();
+            self.previous_pv = best_line.clone();
+            match &mut parallel {
+                None => {
+                    self.negamax(
+                        &mut state,
+                        depth,
+                        0,
+                        -f64::INFINITY,
+                        f64::INFINITY,
+                        true,
+                    );
+                }
+                Some((pool, helpers)) => {
+                    self.split_root(pool, helpers, &state, depth);
+                }
+            }
+            if self.stopped() {
+                break;
+            }
+
+            depth_searched = depth;
+            best_line = self.principal_variation(&state, depth);
+
+            // the first iteration always finishes, so there's a move
+            match limit {
+                SearchLimit::Time(time_limit) => {
+                    if beginning.elapsed() >= *time_limit {
+                        break;
+                    }
+                    self.deadline = Some(beginning + *time_limit);
+                }
+                // an alpha-beta search has no iterations, so the limit is
+                // taken as a number of nodes
+                SearchLimit::Iterations(iterations) => {
+                    if self.nodes >= *iterations as usize {
+                        break;
+                    }
+                    self.node_limit = Some(*iterations as usize);
+                }
+                SearchLimit::Depth(_) => (),
+            }
+            if let Some((_, helpers)) = &mut parallel {
+                for helper in helpers {
+                    helper.deadline = self.deadline;
+                    helper.node_limit = self.node_limit;
+                }
+            }
+        }
+
+        let helper_nodes: usize = parallel
+            .iter()
+            .flat_map(|(_, helpers)| helpers)
+            .map(|helper| helper.nodes)
+            .sum();
+
+        let suggestion = *best_line
+            .first()
+            .expect("Search did not return a potential move");
+
+        SuggestedMove {
+            suggestion,
+            max_depth_considered: depth_searched,
+            time_searching: beginning.elapsed(),
+            total_nodes_considered: self.nodes + helper_nodes,
+            reasoning: self.evaluator.explain(&state, &best_line),
+            expected_line: best_line,
+            table_hits: self.table.hits(),
+            table_misses: self.table.misses(),
+        }
+    }
+
+    // searches the root with the help of other threads. the first move is
+    // searched alone, since it's usually the best, and its score bounds the
+    // searches of the rest, which are shared out between the threads
+    fn split_root(
+        &mut self,
+        pool: &ThreadPool,
+        helpers: &mut [Searcher],
+        state: &State,
+        depth: u8,
+    ) {
+        self.nodes += 1;
+        self.pv[0].clear();
+
+        let key = state.hash();
+        let hash_move = self.table.get(key).and_then(|e| e.best_move);
+        let pv_move = self.previous_pv.first().copied();
+        let mut moves = state.current_possible_moves(state.current_turn);
+        if self.config.move_ordering {
+            moves.sort_by_key(|&m| {
+                Reverse(self.order_key(state, m, 0, pv_move, hash_move))
+            });
+        }
+        let Some((&first, rest)) = moves.split_first() else {
+            return;
+        };
+
+        let mut child = state.clone();
+        child.make_move(first);
+        let first_score = -self.negamax(
+            &mut child,
+            depth - 1,
+            1,
+            -f64::INFINITY,
+            f64::INFINITY,
+            Some(first) == pv_move,
+        );
+        if self.stopped() {
+            return;
+        }
+        let mut best = (first_score, 0, vec![first]);
+        best.2.extend_from_slice(&self.pv[1]);
+
+        // the best score found so far by any thread. a move that can't beat
+        // it fails low, without finding out its exact score
+        let alpha = AtomicU64::new(first_score.to_bits());
+        let mut workers: Vec<&mut Searcher> =
+            iter::once(&mut *self).chain(helpers.iter_mut()).collect();
+        let num_workers = workers.len();
+
+        let results: Vec<Option<(f64, usize, Vec<Move>)>> =
+            pool.install(|| {
+                workers
+                    .par_iter_mut()
+                    .enumerate()
+                    .map(|(worker, searcher)| {
+                        let mut best = None;
+                        let mut child = state.clone();
+                        let moves = rest.iter().enumerate().skip(worker);
+                        for (i, &m) in moves.step_by(num_workers) {
+                            let bound =
+                                f64::from_bits(alpha.load(Ordering::Relaxed));
+                            let undo = child.make_move(m);
+                            let score = -searcher.negamax(
+                                &mut child,
+                                depth - 1,
+                                1,
+                                -f64::INFINITY,
+                                -bound,
+                                false,
+                            );
+                            child.unmake_move(undo);
+                            if searcher.stopped() {
+                                break;
+                            }
+
+                            if score > bound {
+                                raise_bound(&alpha, score);
+                                let mut line = vec![m];
+                                line.extend_from_slice(&searcher.pv[1]);
+                                best = Some((score, i + 1, line));
+                            }
+                        }
+                        best
+                    })
+                    .collect()
+            });
+        if self.stopped() {
+            return;
+        }
+
+        // ties go to the move that was ordered first
+        for (score, i, line) in results.into_iter().flatten() {
+            if score > best.0 || (score == best.0 && i < best.1) {
+                best = (score, i, line);
+            }
+        }
+
+        let (score, _, line) = best;
+        self.table.store(Entry::new(
+            key,
+            depth,
+            score_to_table(score, 0),
+            Bound::Exact,
+            line.first().copied(),
+        ));
+        self.pv[0] = line;
+    }
+
+    // the score of `state` from the point of view of the player to move.
+    // `on_pv` is whether every move leading here was on the previous
+    // iteration's principal variation
+    fn negamax(
+        &mut self,
+        state: &mut State,
+        depth: u8,
+        ply: usize,
+        mut alpha: f64,
+        beta: f64,
+        on_pv: bool,
+    ) -> f64 {
+        if depth == 0 {
+            let quiescence_depth = self.config.quiescence_depth;
+            return self.quiescence(state, quiescence_depth, ply, alpha, beta);
+        }
+        if !self.visit_node(ply) {
+            return 0.0;
+        }
+
+        // the move that ended the game won it for whoever played it, so
+        // -(WIN_SCORE - ply) is the loser's score: a loss `ply` plies from the
+        // root, which the parent negates into the winner's win (sooner wins
+        // score higher). current_turn can't tell whose it is, as a move home
+        // leaves it on the winner's side
+        if !state.active {
+            return -(WIN_SCORE - ply as f64);
+        }
+        // the root still has to be searched to find a move
+        if ply > 0 {
+            if let Some(score) = self.tablebase_score(state, ply) {
+                return score;
+            }
+        }
+        if ply == MAX_PLY {
+            return self.evaluate(state);
+        }
+
+        let key = state.hash();
+        let hash_move = match self.table.probe(key) {
+            Some(entry) => {
+                let score = score_from_table(entry.score, ply);
+                // the root always has to be searched so there's a line
+                let usable = ply > 0
+                    && entry.depth >= depth
+                    && match entry.bound {
+                        Bound::Exact => true,
+                        Bound::Lower => score >= beta,
+                        Bound::Upper => score <= alpha,
+                    };
+                if usable {
+                    return score;
+                }
+                entry.best_move
+            }
+            None => None,
+        };
+
+        let pv_move = match on_pv {
+            true => self.previous_pv.get(ply).copied(),
+            false => None,
+        };
+        let mut moves = state.current_possible_moves(state.current_turn);
+        if self.config.move_ordering {
+            moves.sort_by_key(|&m| {
+                Reverse(self.order_key(state, m, ply, pv_move, hash_move))
+            });
+        } else if let Some(pos) =
+            moves.iter().position(|&m| Some(m) == hash_move)
+        {
+            moves[..=pos].rotate_right(1);
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -f64::INFINITY;
+        let mut best_move = None;
+
+        for m in moves {
+            let is_quiet = !is_win(m) && !is_capture(state, m);
+            let undo = state.make_move(m);
+            let score = -self.negamax(
+                state,
+                depth - 1,
+                ply + 1,
+                -beta,
+                -alpha,
+                on_pv && Some(m) == pv_move,
+            );
+            state.unmake_move(undo);
+
+            if self.stopped() {
+                return 0.0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(m);
+            }
+            if score > alpha {
+                alpha = score;
+                let (line, rest) = self.pv.split_at_mut(ply + 1);
+                line[ply].clear();
+                line[ply].push(m);
+                line[ply].extend_from_slice(&rest[0]);
+            }
+            if alpha >= beta {
+                if is_quiet {
+                    self.record_cutoff(state.current_turn, m, depth, ply);
+                }
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(Entry::new(
+            key,
+            depth,
+            score_to_table(best_score, ply),
+            bound,
+            best_move,
+        ));
+
+        best_score
+    }
+
+    // counts the node and clears its line. returns false once the search has
+    // run out of time or nodes
+    fn visit_node(&mut self, ply: usize) -> bool {
+        self.nodes += 1;
+        self.pv[ply].clear();
+
+        if self.node_limit.is_some_and(|n| self.nodes >= n)
+            || self.nodes.is_multiple_of(NODES_PER_TIME_CHECK)
+                && self.deadline.is_some_and(|d| Instant::now() >= d)
+        {
+            self.stop.store(true, Ordering::Relaxed);
+        }
+        !self.stopped()
+    }
+
+    // searches past the depth limit, but only through wins, captures and
+    // moves that threaten to move home, until the position is quiet or
+    // `depth` runs out. the player to move can otherwise "stand pat" and
+    // take the static evaluation, unless they're about to lose
+    fn quiescence(
+        &mut self,
+        state: &mut State,
+        depth: u8,
+        ply: usize,
+        mut alpha: f64,
+        beta: f64,
+    ) -> f64 {
+        if !self.visit_node(ply) {
+            return 0.0;
+        }
+
+        if !state.active {
+            return -(WIN_SCORE - ply as f64);
+        }
+        if let Some(score) = self.tablebase_score(state, ply) {
+            return score;
+        }
+
+        let stand_pat = self.evaluate(state);
+        if depth == 0 || ply == MAX_PLY {
+            return stand_pat;
+        }
+
+        // if the opponent could move home next turn, every move has to be
+        // looked at to find the ones that stop them
+        let threatened = threatens_home(state, 1 - state.current_turn);
+        let mut best_score = -f64::INFINITY;
+        if !threatened {
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = f64::max(alpha, stand_pat);
+            best_score = stand_pat;
+        }
+
+        let turn = state.current_turn;
+        let mut moves = state.current_possible_moves(turn);
+        moves.sort_by_key(|&m| Reverse(noisy_order(state, m)));
+
+        for m in moves {
+            if !threatened && noisy_order(state, m) == QUIET_ORDER {
+                // moves are sorted, so only quiet ones are left
+                break;
+            }
+
+            let undo = state.make_move(m);
+            let score =
+                -self.quiescence(state, depth - 1, ply + 1, -beta, -alpha);
+            state.unmake_move(undo);
+
+            if self.stopped() {
+                return 0.0;
+            }
+
+            best_score = f64::max(best_score, score);
+            alpha = f64::max(alpha, score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best_score
+    }
+
+    fn order_key(
+        &self,
+        state: &State,
+        m: Move,
+        ply: usize,
+        pv_move: Option<Move>,
+        hash_move: Option<Move>,
+    ) -> (u8, u32) {
+        let (Diagonal(from, to) | Straight(from, to)) = m;
+        let category = if is_win(m) {
+            WIN_ORDER
+        } else if is_capture(state, m) {
+            CAPTURE_ORDER
+        } else if Some(m) == pv_move {
+            PV_ORDER
+        } else if Some(m) == hash_move {
+            HASH_ORDER
+        } else if self.killers[ply].contains(&Some(m)) {
+            KILLER_ORDER
+        } else {
+            QUIET_ORDER
+        };
+        let history = self.history[state.current_turn as usize][from][to];
+        (category, history)
+    }
+
+    fn record_cutoff(&mut self, turn: u8, m: Move, depth: u8, ply: usize) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(m) {
+            killers[1] = killers[0];
+            killers[0] = Some(m);
+        }
+
+        let (Diagonal(from, to) | Straight(from, to)) = m;
+        let history = &mut self.history[turn as usize][from][to];
+        *history = history.saturating_add(depth as u32 * depth as u32);
+    }
+
+    // the exact score of a position the tablebase covers, with the win or
+    // loss as far from the root as it really is
+    fn tablebase_score(&self, state: &State, ply: usize) -> Option<f64> {
+        if !self.config.tablebase {
+            return None;
+        }
+        let plies = TABLEBASE.as_ref()?.probe(state)?;
+        let score = WIN_SCORE - (ply + plies as usize) as f64;
+        // the player to move makes the last move when it's an odd number away
+        Some(if plies % 2 == 1 { score } else { -score })
+    }
+
+    fn evaluate(&self, state: &State) -> f64 {
+        let score = self.evaluator.score(state);
+        match state.current_turn {
+            0 => score,
+            _ => -score,
+        }
+    }
+
+    // the line stops early wherever the table cut the search off, so it's
+    // finished with the moves the table remembers
+    fn principal_variation(&self, state: &State, depth: u8) -> Vec<Move> {
+        let mut line = self.pv[0].clone();
+        let mut state = state.clone();
+        for &m in &line {
+            state.make_move(m);
+        }
+
+        while line.len() < depth as usize {
+            let Some(m) =
+                self.table.get(state.hash()).and_then(|e| e.best_move)
+            else {
+                break;
+            };
+            if !state
+                .current_possible_moves(state.current_turn)
+                .contains(&m)
+            {
+                break;
+            }
+            state.make_move(m);
+            line.push(m);
+        }
+
+        line
+    }
+}
+
+// the pool of `threads` threads, started the first time it's needed
+fn thread_pool(threads: usize) -> Arc<ThreadPool> {
+    let mut pools = THREAD_POOLS.lock().unwrap();
+    let pool = pools.entry(threads).or_insert_with(|| {
+        Arc::new(
+            ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("could not start the search threads"),
+        )
+    });
+    Arc::clone(pool)
+}
+
+// raises the bound shared between threads to `score`, unless another thread
+// has already found something better
+fn raise_bound(bound: &AtomicU64, score: f64) {
+    let _ = bound.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+        (score > f64::from_bits(bits)).then_some(score.to_bits())
+    });
+}
+
+fn is_win(m: Move) -> bool {
+    let (Diagonal(_, to) | Straight(_, to)) = m;
+    to == 0 || to == 36
+}
+
+// how interesting a move is to the quiescence search, or QUIET_ORDER if it
+// isn't searched there at all
+fn noisy_order(state: &State, m: Move) -> u8 {
+    let (Diagonal(_, to) | Straight(_, to)) = m;
+    if is_win(m) {
+        WIN_ORDER
+    } else if is_capture(state, m) {
+        CAPTURE_ORDER
+    } else if reaches_home_from(state.current_turn, to) {
+        THREAT_ORDER
+    } else {
+        QUIET_ORDER
+    }
+}
+
+// whether a piece of `turn` on `hex` could move home, were the way clear
+fn reaches_home_from(turn: u8, hex: usize) -> bool {
+    let home = if turn == 0 { 36 } else { 0 };
+    let masks = get_moves_of_piece(turn, &hex);
+    (masks.diagonal | masks.straight).contains(home)
+}
+
+// whether `turn` has a move home available on the current board
+fn threatens_home(state: &State, turn: u8) -> bool {
+    state
+        .board
+        .current_players_pieces(turn)
+        .iter()
+        .any(|hex| reaches_home_from(turn, hex))
+        && state.board.all_valid_moves(turn).iter().any(|&m| is_win(m))
+}
+
+// a straight move can only land on an occupied hex by capturing
+fn is_capture(state: &State, m: Move) -> bool {
+    match m {
+        Straight(_, to) => state.board.occupied().contains(to),
+        Diagonal(..) => false,
+    }
+}
+
+// wins are stored relative to the position they're found from, rather than
+// the root, so they stay correct when the position is reached at another ply
+fn score_to_table(score: f64, ply: usize) -> f64 {
+    if score >= WIN_SCORE - MAX_PLY as f64 {
+        score + ply as f64
+    } else if score <= -(WIN_SCORE - MAX_PLY as f64) {
+        score - ply as f64
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: f64, ply: usize) -> f64 {
+    if score >= WIN_SCORE - MAX_PLY as f64 {
+        score - ply as f64
+    } else if score <= -(WIN_SCORE - MAX_PLY as f64) {
+        score + ply as f64
+    } else {
+        score
+    }
+}
+
+pub struct SuggestedMove {
+    pub suggestion: Move,
+    pub(crate) max_depth_considered: u8,
+    pub(crate) time_searching: Duration,
+    pub(crate) total_nodes_considered: usize,
+    pub(crate) reasoning: String,
+    pub(crate) expected_line: Vec<Move>,
+    pub(crate) table_hits: usize,
+    pub(crate) table_misses: usize,
+}
+
+impl SuggestedMove {
+    pub fn max_depth_considered(&self) -> u8 {
+        self.max_depth_considered
+    }
+
+    pub fn total_nodes_considered(&self) -> usize {
+        self.total_nodes_considered
+    }
+
+    pub fn expected_line(&self) -> &[Move] {
+        &self.expected_line
+    }
+}
+
+impl Debug for SuggestedMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "AI suggested {:?} ({} nodes considered, depth of {}, {} table hits / {} misses) in {:.2} seconds with reasoning: {}. Expected the sequence: {:?}",
+            self.suggestion,
+            self.total_nodes_considered.separated_string(),
+            self.max_depth_considered,
+            self.table_hits.separated_string(),
+            self.table_misses.separated_string(),
+            self.time_searching.as_secs_f32(),
+            self.reasoning,
+            self.expected_line
+        ))
+    }
+}
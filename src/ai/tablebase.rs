@@ -0,0 +1,366 @@
+use lazy_static::lazy_static;
+use log::{info, warn};
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::game::bitboard::{Bitboard, NUM_HEXES};
+use crate::game::board::{
+    Board,
+    Move::{Diagonal, Straight},
+};
+use crate::game::gamestate::State;
+
+/// how many pieces per side a tablebase covers unless told otherwise
+pub const DEFAULT_MAX_PIECES: usize = 2;
+
+/// the most pieces per side a tablebase can cover (3 a side already takes
+/// about 120MB, and 4 would take nearly 9GB)
+pub const MAX_PIECES: usize = 3;
+
+/// what `HeuristicWeights::score` gives a position the tablebase knows to be
+/// won (less the distance to the win), well clear of any heuristic score
+pub const TABLEBASE_WIN: f64 = 1e12;
+
+const MAGIC: &[u8; 4] = b"MSTB";
+const VERSION: u8 = 1;
+
+// the row of each hex, counting from black's side of the board
+const ROWS: [u8; NUM_HEXES] = [
+    0, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 5, 5, 5, 5, 6, 6, 6, 7, 7, 7, 7, 8,
+    8, 8, 9, 9, 9, 9, 10, 10, 10, 11, 11, 12,
+];
+const LAST_ROW: u8 = 12;
+
+// BINOMIAL[n][k] is n choose k
+const BINOMIAL: [[usize; NUM_HEXES + 1]; NUM_HEXES + 1] = binomials();
+
+const fn binomials() -> [[usize; NUM_HEXES + 1]; NUM_HEXES + 1] {
+    let mut table = [[0; NUM_HEXES + 1]; NUM_HEXES + 1];
+    let mut n = 0;
+    while n <= NUM_HEXES {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+}
+
+lazy_static! {
+    /// the tablebase at `TABLEBASE_PATH`, if there is one
+    pub static ref TABLEBASE: Option<Tablebase> =
+        env::var("TABLEBASE_PATH").ok().and_then(|path| {
+            match Tablebase::load(&path) {
+                Ok(tablebase) => {
+                    info!(
+                        "Using the tablebase at {path} (up to {} pieces per side)",
+                        tablebase.max_pieces
+                    );
+                    Some(tablebase)
+                }
+                Err(e) => {
+                    warn!("Could not load the tablebase at {path} ({e})");
+                    None
+                }
+            }
+        });
+}
+
+/// the exact result of every position with up to `max_pieces` pieces per
+/// side. a position is stored as the number of plies until the game ends
+/// with perfect play (0 if it can't come up in a game). since only a move
+/// can end the game, and the player making it wins, the player to move wins
+/// exactly when that number is odd
+///
+/// on disk, a tablebase is `MSTB`, a version byte and `max_pieces`, followed
+/// by one byte per position for every pair of piece counts
+pub struct Tablebase {
+    max_pieces: usize,
+    // indexed by table_idx()
+    tables: Vec<Vec<u8>>,
+}
+
+impl Tablebase {
+    // generate() solves every table, fewest pieces first, so the results of
+    // captures are known before they're needed. within a table, pieces only
+    // move forward, so positions are solved from the most advanced back
+    pub fn generate(max_pieces: usize) -> Tablebase {
+        assert!(
+            (1..=MAX_PIECES).contains(&max_pieces),
+            "tablebases cover 1 to {MAX_PIECES} pieces per side"
+        );
+
+        let combinations: Vec<Vec<Bitboard>> =
+            (0..=max_pieces).map(combinations_of).collect();
+        let mut tablebase = Tablebase {
+            max_pieces,
+            tables: vec![vec![]; max_pieces * max_pieces],
+        };
+
+        for total in 2..=2 * max_pieces {
+            for black in 1..=max_pieces {
+                let white = match total.checked_sub(black) {
+                    Some(w) if (1..=max_pieces).contains(&w) => w,
+                    _ => continue,
+                };
+                let idx = tablebase.table_idx(black, white);
+                tablebase.tables[idx] = vec![0; table_len(black, white)];
+                tablebase.solve_table(
+                    &combinations[black],
+                    &combinations[white],
+                    black,
+                    white,
+                );
+                info!(
+                    "Solved the tablebase for {black} black vs {white} white"
+                );
+            }
+        }
+
+        tablebase
+    }
+
+    fn solve_table(
+        &mut self,
+        black_sets: &[Bitboard],
+        white_sets: &[Bitboard],
+        num_black: usize,
+        num_white: usize,
+    ) {
+        // how far each side's pieces have moved from its own edge
+        let black_advance = |b: Bitboard| -> usize {
+            b.iter().map(|hex| ROWS[hex] as usize).sum()
+        };
+        let white_advance = |w: Bitboard| -> usize {
+            w.iter().map(|hex| (LAST_ROW - ROWS[hex]) as usize).sum()
+        };
+
+        let mut white_by_advance =
+            vec![vec![]; LAST_ROW as usize * num_white + 1];
+        for &w in white_sets {
+            white_by_advance[white_advance(w)].push(w);
+        }
+
+        let max_advance = LAST_ROW as usize * (num_black + num_white);
+        for advance in (0..=max_advance).rev() {
+            for &b in black_sets {
+                let Some(whites) = advance
+                    .checked_sub(black_advance(b))
+                    .and_then(|a| white_by_advance.get(a))
+                else {
+                    continue;
+                };
+                for &w in whites {
+                    for turn in 0..2 {
+                        let value = self.solve_position(b, w, turn);
+                        let idx = self.table_idx(num_black, num_white);
+                        self.tables[idx][position_idx(b, w, num_white, turn)] =
+                            value;
+                    }
+                }
+            }
+        }
+    }
+
+    // the stored value of a position, given that everything it can lead to
+    // has been solved
+    fn solve_position(&self, black: Bitboard, white: Bitboard, turn: u8) -> u8 {
+        // overlapping pieces, or a game that's already been won
+        if !(black & white).is_empty()
+            || black.contains(36)
+            || white.contains(0)
+        {
+            return 0;
+        }
+
+        let board = Board::from_pieces(black, white);
+        let moves = board.all_valid_moves(turn);
+        let mut fastest_win: Option<u8> = None;
+        let mut slowest_loss: Option<u8> = None;
+
+        for m in moves {
+            let (Diagonal(from, to) | Straight(from, to)) = m;
+            if to == 0 || to == 36 {
+                return 1;
+            }
+
+            let mut child = board;
+            child.apply_move(from, to, turn);
+            let opponent = 1 - turn;
+            if child.current_players_pieces(opponent).is_empty()
+                || !child.has_valid_move(opponent)
+            {
+                return 1;
+            }
+
+            let plies = 1 + self
+                .probe_board(&child, opponent)
+                .expect("positions are solved after the ones they lead to");
+            if plies % 2 == 1 {
+                fastest_win = Some(fastest_win.map_or(plies, |p| p.min(plies)));
+            } else {
+                slowest_loss =
+                    Some(slowest_loss.map_or(plies, |p| p.max(plies)));
+            }
+        }
+
+        // no moves means the game was already over
+        fastest_win.or(slowest_loss).unwrap_or(0)
+    }
+
+    fn table_idx(&self, num_black: usize, num_white: usize) -> usize {
+        (num_black - 1) * self.max_pieces + (num_white - 1)
+    }
+
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    // probe() gives the number of plies until the game ends with perfect
+    // play, if the tablebase covers the position
+    pub fn probe(&self, state: &State) -> Option<u8> {
+        if !state.active {
+            return None;
+        }
+        self.probe_board(&state.board, state.current_turn)
+    }
+
+    pub fn probe_board(&self, board: &Board, turn: u8) -> Option<u8> {
+        let black = board.current_players_pieces(0);
+        let white = board.current_players_pieces(1);
+        let (num_black, num_white) = (black.count(), white.count());
+        if !(1..=self.max_pieces).contains(&num_black)
+            || !(1..=self.max_pieces).contains(&num_white)
+        {
+            return None;
+        }
+
+        let table = &self.tables[self.table_idx(num_black, num_white)];
+        match table[position_idx(black, white, num_white, turn)] {
+            0 => None,
+            plies => Some(plies),
+        }
+    }
+
+    // the score of a position from black's point of view, as
+    // HeuristicWeights::score would give it
+    pub fn score(&self, state: &State) -> Option<f64> {
+        let plies = self.probe(state)?;
+        let black_wins = (plies % 2 == 1) == (state.current_turn == 0);
+        let score = TABLEBASE_WIN - plies as f64;
+        Some(if black_wins { score } else { -score })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION, self.max_pieces as u8])?;
+        for table in &self.tables {
+            file.write_all(table)?;
+        }
+        file.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Tablebase> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut file = BufReader::new(file);
+        let mut header = [0; 6];
+        file.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a milestone tablebase (or from another version)",
+            ));
+        }
+
+        // checked before allocating the tables it claims to have
+        let max_pieces = header[5] as usize;
+        if !(1..=MAX_PIECES).contains(&max_pieces) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("a tablebase can't cover {max_pieces} pieces per side"),
+            ));
+        }
+        let expected_len = header.len() + tablebase_len(max_pieces);
+        if file_len != expected_len as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "a tablebase of up to {max_pieces} pieces per side is \
+                     {expected_len} bytes, not {file_len}"
+                ),
+            ));
+        }
+
+        let mut tablebase = Tablebase {
+            max_pieces,
+            tables: vec![],
+        };
+        for black in 1..=max_pieces {
+            for white in 1..=max_pieces {
+                let mut table = vec![0; table_len(black, white)];
+                file.read_exact(&mut table)?;
+                tablebase.tables.push(table);
+            }
+        }
+        Ok(tablebase)
+    }
+}
+
+// every set of `k` hexes
+fn combinations_of(k: usize) -> Vec<Bitboard> {
+    let mut sets = vec![];
+    let mut hexes: Vec<usize> = (0..k).collect();
+    loop {
+        sets.push(Bitboard::from_hexes(&hexes));
+
+        // advance to the next combination, rightmost hex first
+        let Some(i) = (0..k).rev().find(|&i| hexes[i] < NUM_HEXES - k + i)
+        else {
+            return sets;
+        };
+        hexes[i] += 1;
+        for j in i + 1..k {
+            hexes[j] = hexes[j - 1] + 1;
+        }
+    }
+}
+
+// the position of a set of hexes among all sets of the same size
+// (the combinatorial number system)
+fn rank(set: Bitboard) -> usize {
+    set.iter()
+        .enumerate()
+        .map(|(i, hex)| BINOMIAL[hex][i + 1])
+        .sum()
+}
+
+fn table_len(num_black: usize, num_white: usize) -> usize {
+    BINOMIAL[NUM_HEXES][num_black] * BINOMIAL[NUM_HEXES][num_white] * 2
+}
+
+// the size of every table of a tablebase, together
+fn tablebase_len(max_pieces: usize) -> usize {
+    (1..=max_pieces)
+        .flat_map(|black| {
+            (1..=max_pieces).map(move |white| table_len(black, white))
+        })
+        .sum()
+}
+
+fn position_idx(
+    black: Bitboard,
+    white: Bitboard,
+    num_white: usize,
+    turn: u8,
+) -> usize {
+    let white_sets = BINOMIAL[NUM_HEXES][num_white];
+    (rank(black) * white_sets + rank(white)) * 2 + turn as usize
+}
@@ -0,0 +1,333 @@
+use log::{info, warn};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rusqlite::{Connection, Result};
+
+use crate::game::bitboard::NUM_HEXES;
+use crate::game::gamestate::State;
+
+use super::mlp::{encode, Activation, Mlp};
+
+/// a position and how often black won the games it came up in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub state: String,
+    pub input: [f64; NUM_HEXES],
+    pub target: f64,
+}
+
+/// loads every position in `state_table` (or only those from games in
+/// `dataset`), with the fraction of its games black won as the target (the
+/// same as `neuralnet/train.py`)
+pub fn load_samples(
+    conn: &Connection,
+    dataset: Option<&str>,
+) -> Result<Vec<Sample>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT state_table.state,
+            1.0 - CAST(SUM(game_table.result) AS REAL) / COUNT(*)
+        FROM state_table
+        JOIN game_table ON state_table.game_id = game_table.game_id
+        WHERE ?1 IS NULL OR game_table.dataset = ?1
+        GROUP BY state_table.state
+        "#,
+    )?;
+    let rows = stmt.query_map([dataset], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+    })?;
+
+    let mut samples = vec![];
+    for row in rows {
+        let (state, target) = row?;
+        match State::from_repr_string(&state) {
+            Ok(s) => samples.push(Sample {
+                input: encode(&s.board),
+                state,
+                target,
+            }),
+            Err(_) => warn!("Skipping a state that couldn't be read ({state})"),
+        }
+    }
+    Ok(samples)
+}
+
+/// how a network is trained. the defaults are those of sklearn's
+/// `MLPRegressor`, other than the number of epochs
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainConfig {
+    pub hidden_layers: Vec<usize>,
+    pub activation: Activation,
+    pub epochs: usize,
+    pub batch_size: usize,
+    // adam's step size
+    pub learning_rate: f64,
+    // the strength of the L2 penalty on the weights
+    pub l2: f64,
+    // the fraction of positions held out to measure the validation loss
+    pub validation_fraction: f64,
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        TrainConfig {
+            hidden_layers: vec![100],
+            activation: Activation::Relu,
+            epochs: 50,
+            batch_size: 200,
+            learning_rate: 0.001,
+            l2: 0.0001,
+            validation_fraction: 0.25,
+        }
+    }
+}
+
+/// the mean squared errors after an epoch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EpochLoss {
+    pub train: f64,
+    pub validation: f64,
+}
+
+const BETA_1: f64 = 0.9;
+const BETA_2: f64 = 0.999;
+const EPSILON: f64 = 1e-8;
+
+// the first and second moment estimates of adam, shaped like the network
+struct Moments {
+    coefs: Vec<Vec<Vec<(f64, f64)>>>,
+    intercepts: Vec<Vec<(f64, f64)>>,
+}
+
+// train() fits a network to `samples` with minibatch adam, returning the
+// network from the epoch with the lowest validation loss, and the losses
+// after every epoch
+pub fn train<R: Rng>(
+    samples: &[Sample],
+    config: &TrainConfig,
+    rng: &mut R,
+) -> (Mlp, Vec<EpochLoss>) {
+    let mut shuffled: Vec<&Sample> = samples.iter().collect();
+    shuffled.shuffle(rng);
+    let num_validation =
+        (samples.len() as f64 * config.validation_fraction).round() as usize;
+    let (validation, training) = shuffled.split_at(num_validation);
+    let mut training = training.to_vec();
+    assert!(!training.is_empty(), "there's nothing to train on");
+
+    let mut mlp = initialize(config, rng);
+    let mut moments = Moments {
+        coefs: zeroed_coefs(&mlp, (0.0, 0.0)),
+        intercepts: zeroed_intercepts(&mlp, (0.0, 0.0)),
+    };
+    let mut step = 0;
+
+    let mut best: Option<(Mlp, f64)> = None;
+    let mut losses = vec![];
+    for epoch in 1..=config.epochs {
+        training.shuffle(rng);
+        for batch in training.chunks(config.batch_size.max(1)) {
+            step += 1;
+            let (coef_grads, intercept_grads) = gradients(&mlp, batch, config);
+            adam_step(
+                &mut mlp,
+                &mut moments,
+                &coef_grads,
+                &intercept_grads,
+                config.learning_rate,
+                step,
+            );
+        }
+
+        let loss = EpochLoss {
+            train: mean_squared_error(&mlp, &training),
+            validation: match validation.is_empty() {
+                true => f64::NAN,
+                false => mean_squared_error(&mlp, validation),
+            },
+        };
+        info!(
+            "Epoch {epoch}: train loss {:.5}, validation loss {:.5}",
+            loss.train, loss.validation
+        );
+        losses.push(loss);
+
+        // without a validation set, the last network is kept
+        let score = match loss.validation.is_nan() {
+            true => f64::NEG_INFINITY,
+            false => loss.validation,
+        };
+        if best
+            .as_ref()
+            .is_none_or(|(_, best_score)| score <= *best_score)
+        {
+            best = Some((mlp.clone(), score));
+        }
+    }
+
+    let model = best.map_or(mlp, |(model, _)| model);
+    (model, losses)
+}
+
+// glorot initialization, as sklearn does it
+fn initialize<R: Rng>(config: &TrainConfig, rng: &mut R) -> Mlp {
+    let mut sizes = vec![NUM_HEXES];
+    sizes.extend(&config.hidden_layers);
+    sizes.push(1);
+
+    let factor = match config.activation {
+        Activation::Logistic => 2.0,
+        _ => 6.0,
+    };
+    let mut coefs = vec![];
+    let mut intercepts = vec![];
+    for layer in sizes.windows(2) {
+        let (inputs, outputs) = (layer[0], layer[1]);
+        let bound = (factor / (inputs + outputs) as f64).sqrt();
+        coefs.push(
+            (0..inputs)
+                .map(|_| {
+                    (0..outputs).map(|_| rng.gen_range(-bound..bound)).collect()
+                })
+                .collect(),
+        );
+        intercepts
+            .push((0..outputs).map(|_| rng.gen_range(-bound..bound)).collect());
+    }
+
+    Mlp {
+        activation: config.activation,
+        out_activation: Activation::Identity,
+        coefs,
+        intercepts,
+    }
+}
+
+// something for every weight of the network, all starting at `zero`
+fn zeroed_coefs<T: Copy>(mlp: &Mlp, zero: T) -> Vec<Vec<Vec<T>>> {
+    mlp.coefs
+        .iter()
+        .map(|layer| layer.iter().map(|w| vec![zero; w.len()]).collect())
+        .collect()
+}
+
+fn zeroed_intercepts<T: Copy>(mlp: &Mlp, zero: T) -> Vec<Vec<T>> {
+    mlp.intercepts.iter().map(|b| vec![zero; b.len()]).collect()
+}
+
+/// the gradients of half the mean squared error over `batch` (plus the L2
+/// penalty), by backpropagation, shaped like the network's coefs and
+/// intercepts
+pub fn gradients(
+    mlp: &Mlp,
+    batch: &[&Sample],
+    config: &TrainConfig,
+) -> (Vec<Vec<Vec<f64>>>, Vec<Vec<f64>>) {
+    let mut coef_grads = zeroed_coefs(mlp, 0.0);
+    let mut intercept_grads = zeroed_intercepts(mlp, 0.0);
+    let last = mlp.coefs.len() - 1;
+
+    for sample in batch {
+        let outputs = mlp.forward(&sample.input);
+        let prediction = outputs[last + 1][0];
+        let mut deltas = vec![
+            (prediction - sample.target)
+                * mlp.out_activation.derivative(prediction),
+        ];
+
+        for layer in (0..=last).rev() {
+            let inputs = &outputs[layer];
+            for (j, &input) in inputs.iter().enumerate() {
+                if input == 0.0 {
+                    continue;
+                }
+                for (grad, delta) in
+                    coef_grads[layer][j].iter_mut().zip(&deltas)
+                {
+                    *grad += input * delta;
+                }
+            }
+            for (grad, delta) in intercept_grads[layer].iter_mut().zip(&deltas)
+            {
+                *grad += delta;
+            }
+
+            if layer > 0 {
+                deltas = mlp.coefs[layer]
+                    .iter()
+                    .zip(inputs)
+                    .map(|(weights, &input)| {
+                        let back: f64 = weights
+                            .iter()
+                            .zip(&deltas)
+                            .map(|(w, d)| w * d)
+                            .sum();
+                        back * mlp.activation.derivative(input)
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    let n = batch.len() as f64;
+    for (layer, grads) in mlp.coefs.iter().zip(coef_grads.iter_mut()) {
+        for (weights, grads) in layer.iter().zip(grads.iter_mut()) {
+            for (w, grad) in weights.iter().zip(grads.iter_mut()) {
+                *grad = (*grad + config.l2 * w) / n;
+            }
+        }
+    }
+    for grads in intercept_grads.iter_mut() {
+        for grad in grads.iter_mut() {
+            *grad /= n;
+        }
+    }
+    (coef_grads, intercept_grads)
+}
+
+fn adam_step(
+    mlp: &mut Mlp,
+    moments: &mut Moments,
+    coef_grads: &[Vec<Vec<f64>>],
+    intercept_grads: &[Vec<f64>],
+    learning_rate: f64,
+    step: i32,
+) {
+    let rate = learning_rate * (1.0 - BETA_2.powi(step)).sqrt()
+        / (1.0 - BETA_1.powi(step));
+    let update = |param: &mut f64, moment: &mut (f64, f64), grad: f64| {
+        moment.0 = BETA_1 * moment.0 + (1.0 - BETA_1) * grad;
+        moment.1 = BETA_2 * moment.1 + (1.0 - BETA_2) * grad * grad;
+        *param -= rate * moment.0 / (moment.1.sqrt() + EPSILON);
+    };
+
+    for ((layer, moments), grads) in
+        mlp.coefs.iter_mut().zip(&mut moments.coefs).zip(coef_grads)
+    {
+        for ((weights, moments), grads) in
+            layer.iter_mut().zip(moments).zip(grads)
+        {
+            for ((w, m), &g) in weights.iter_mut().zip(moments).zip(grads) {
+                update(w, m, g);
+            }
+        }
+    }
+    for ((biases, moments), grads) in mlp
+        .intercepts
+        .iter_mut()
+        .zip(&mut moments.intercepts)
+        .zip(intercept_grads)
+    {
+        for ((b, m), &g) in biases.iter_mut().zip(moments).zip(grads) {
+            update(b, m, g);
+        }
+    }
+}
+
+fn mean_squared_error(mlp: &Mlp, samples: &[&Sample]) -> f64 {
+    let total: f64 = samples
+        .iter()
+        .map(|sample| (mlp.predict(&sample.input) - sample.target).powi(2))
+        .sum();
+    total / samples.len() as f64
+}
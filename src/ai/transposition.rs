@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::game::board::Move;
+
+/// how many entries a table holds unless told otherwise
+pub const DEFAULT_TABLE_SIZE: usize = 1 << 16;
+
+/// how a stored score relates to the true value of its position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    // the search failed high, the true value is at least the score
+    Lower,
+    // the search failed low, the true value is at most the score
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    pub key: u64,
+    pub depth: u8,
+    pub score: f64,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+    generation: u8,
+}
+
+impl Entry {
+    pub fn new(
+        key: u64,
+        depth: u8,
+        score: f64,
+        bound: Bound,
+        best_move: Option<Move>,
+    ) -> Entry {
+        Entry {
+            key,
+            depth,
+            score,
+            bound,
+            best_move,
+            generation: 0,
+        }
+    }
+}
+
+/// a fixed-size table of search results, indexed by zobrist hash. every
+/// slot has its own lock, so one table can be shared by the threads of a
+/// parallel search
+#[derive(Debug)]
+pub struct TranspositionTable {
+    entries: Vec<Mutex<Option<Entry>>>,
+    generation: AtomicU8,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_TABLE_SIZE)
+    }
+}
+
+impl TranspositionTable {
+    // the size is rounded up to a power of two so slots can be masked
+    pub fn new(size: usize) -> TranspositionTable {
+        TranspositionTable {
+            entries: (0..size.max(1).next_power_of_two())
+                .map(|_| Mutex::new(None))
+                .collect(),
+            generation: AtomicU8::new(0),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    fn slot(&self, key: u64) -> MutexGuard<'_, Option<Entry>> {
+        let idx = key as usize & (self.entries.len() - 1);
+        // an entry is always written whole, so a panic elsewhere can't
+        // leave it half-updated
+        self.entries[idx]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // new_search() marks every stored entry as coming from an older search,
+    // so they're the first to be replaced
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let entry = self.get(key);
+        match entry {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        entry
+    }
+
+    // like probe(), without counting towards the hits and misses
+    pub fn get(&self, key: u64) -> Option<Entry> {
+        self.slot(key).filter(|entry| entry.key == key)
+    }
+
+    // an existing entry is only replaced by a search that is at least as
+    // deep, unless it's from an older search or about the same position
+    pub fn store(&self, mut entry: Entry) {
+        entry.generation = self.generation.load(Ordering::Relaxed);
+        let mut slot = self.slot(entry.key);
+
+        let replace = match *slot {
+            None => true,
+            Some(existing) => {
+                existing.key == entry.key
+                    || existing.generation != entry.generation
+                    || entry.depth >= existing.depth
+            }
+        };
+
+        if replace {
+            *slot = Some(entry);
+        }
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
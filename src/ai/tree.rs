@@ -1,17 +1,20 @@
+use lazy_static::lazy_static;
+use log::info;
 use petgraph::dot::Dot;
 use petgraph::graph::DiGraph;
 
 use petgraph::graph::NodeIndex;
 use petgraph::visit::EdgeRef;
-use separator::Separatable;
 use serde::Deserialize;
 use serde::Serialize;
 
-use std::fmt::Debug;
+use std::env;
+use std::fmt::{self, Debug};
 use std::fs::File;
 use std::io::Write;
 use std::process::Command;
 use std::process::Stdio;
+use std::str::FromStr;
 
 use std::time::Instant;
 
@@ -19,13 +22,28 @@
 use std::ops::Index;
 use std::time::Duration;
 
-use crate::ai::heuristics::HeuristicWeightsWithTwoStates;
 use crate::game::board::Move;
 
 use super::super::game::board::Move::{Diagonal, Straight};
 use super::super::game::gamestate::State;
+use super::evaluator::Evaluator;
 use super::heuristics::HeuristicWeights;
 use super::heuristics::Weights;
+pub use super::search::SuggestedMove;
+use super::search::{SearchConfig, Searcher};
+use super::transposition::{Bound, Entry, TranspositionTable};
+
+lazy_static! {
+    // when set, searches build a GameTree and draw it to output.svg
+    static ref DEBUG_TREE: bool =
+        env::var("DEBUG_TREE").is_ok_and(|elt| match elt.parse() {
+            Ok(b) => {
+                info!("Using DEBUG_TREE environment variable ({})", b);
+                b
+            }
+            _ => false,
+        });
+}
 
 #[derive(Debug, Clone)]
 pub struct GameTree {
@@ -104,33 +122,106 @@ fn add_all_possible_children(&mut self, root: NodeIndex) {
         }
     }
 
+    // looks this node up in the table, returning a score if the stored
+    // result is deep and tight enough to stand in for searching it, along
+    // with the best move found the last time it was searched
+    fn probe_table(
+        &self,
+        node_idx: NodeIndex,
+        alpha: f64,
+        beta: f64,
+        table: &mut TranspositionTable,
+    ) -> (Option<f64>, Option<Move>) {
+        let node = &self.tree[node_idx];
+        let Some(entry) = table.probe(node.state.hash()) else {
+            return (None, None);
+        };
+
+        // the root always has to be searched so the expected line is known
+        let usable = node_idx != self.tree_root_idx
+            && entry.depth >= self.max_depth - node.depth
+            && match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.score >= beta,
+                Bound::Upper => entry.score <= alpha,
+            };
+
+        (usable.then_some(entry.score), entry.best_move)
+    }
+
+    fn store_in_table(
+        &self,
+        node_idx: NodeIndex,
+        score: f64,
+        bound: Bound,
+        best_move: Option<Move>,
+        table: &mut TranspositionTable,
+    ) {
+        let node = &self.tree[node_idx];
+        table.store(Entry::new(
+            node.state.hash(),
+            self.max_depth - node.depth,
+            score,
+            bound,
+            best_move,
+        ));
+    }
+
+    // the children of a node, with the move the table remembers as best
+    // searched first
+    fn ordered_children(
+        &self,
+        root_idx: NodeIndex,
+        hash_move: Option<Move>,
+    ) -> Vec<(NodeIndex, Move)> {
+        let mut children: Vec<(NodeIndex, Move)> = self
+            .tree
+            .edges(root_idx)
+            .map(|e| (e.target(), *e.weight()))
+            .collect();
+        if let Some(pos) =
+            children.iter().position(|&(_, m)| Some(m) == hash_move)
+        {
+            children[..=pos].rotate_right(1);
+        }
+        children
+    }
+
     fn max_value(
         &mut self,
         root_idx: NodeIndex,
         mut alpha: f64,
         beta: f64,
+        table: &mut TranspositionTable,
     ) -> (f64, Option<Move>) {
+        let (stored_score, hash_move) =
+            self.probe_table(root_idx, alpha, beta, table);
+        if let Some(score) = stored_score {
+            return (score, hash_move);
+        }
+
         self.add_all_possible_children(root_idx);
 
         // this node is a leaf node / at max-depth:
         if self.tree.edges(root_idx).peekable().peek().is_none() {
             let root_node = self.tree.index(root_idx);
-            (root_node.evaluate(self), None)
+            let score = root_node.evaluate(self);
+            self.store_in_table(root_idx, score, Bound::Exact, None, table);
+            (score, None)
         } else {
             match self.tree[root_idx].best_child_node {
-                Some((n, _)) => self.min_value(n, alpha, beta),
+                Some((n, _)) => self.min_value(n, alpha, beta, table),
                 None => {
                     // to maximize this node, minimize its children
+                    let original_alpha = alpha;
                     let mut best_score = f64::MIN;
                     let mut best_move: Option<Move> = None;
 
-                    let outgoing_edges: Vec<(NodeIndex, Move)> = self
-                        .tree
-                        .edges(root_idx)
-                        .map(|e| (e.target(), *e.weight()))
-                        .collect();
+                    let outgoing_edges =
+                        self.ordered_children(root_idx, hash_move);
                     for (dest, mv) in outgoing_edges {
-                        let new_score = self.min_value(dest, alpha, beta).0;
+                        let new_score =
+                            self.min_value(dest, alpha, beta, table).0;
                         let new_move = Some(mv);
 
                         if new_score >= best_score {
@@ -147,6 +238,17 @@ fn max_value(
                         }
                     }
 
+                    let bound = if best_score <= original_alpha {
+                        Bound::Upper
+                    } else if best_score >= beta {
+                        Bound::Lower
+                    } else {
+                        Bound::Exact
+                    };
+                    self.store_in_table(
+                        root_idx, best_score, bound, best_move, table,
+                    );
+
                     (best_score, best_move)
                 }
             }
@@ -158,28 +260,36 @@ fn min_value(
         root_idx: NodeIndex,
         alpha: f64,
         mut beta: f64,
+        table: &mut TranspositionTable,
     ) -> (f64, Option<Move>) {
+        let (stored_score, hash_move) =
+            self.probe_table(root_idx, alpha, beta, table);
+        if let Some(score) = stored_score {
+            return (score, hash_move);
+        }
+
         self.add_all_possible_children(root_idx);
 
         // this node is a leaf node / at max-depth:
         if self.tree.edges(root_idx).peekable().peek().is_none() {
             let root_node = self.tree.index(root_idx);
-            (root_node.evaluate(self), None)
+            let score = root_node.evaluate(self);
+            self.store_in_table(root_idx, score, Bound::Exact, None, table);
+            (score, None)
         } else {
             match self.tree[root_idx].best_child_node {
-                Some((n, _)) => self.max_value(n, alpha, beta),
+                Some((n, _)) => self.max_value(n, alpha, beta, table),
                 None => {
                     // to minimize this node, maximize its children
+                    let original_beta = beta;
                     let mut best_score = f64::MAX;
                     let mut best_move: Option<Move> = None;
 
-                    let outgoing_edges: Vec<(NodeIndex, Move)> = self
-                        .tree
-                        .edges(root_idx)
-                        .map(|e| (e.target(), *e.weight()))
-                        .collect();
+                    let outgoing_edges =
+                        self.ordered_children(root_idx, hash_move);
                     for (dest, mv) in outgoing_edges {
-                        let new_score = self.max_value(dest, alpha, beta).0;
+                        let new_score =
+                            self.max_value(dest, alpha, beta, table).0;
                         let new_move = Some(mv);
 
                         if new_score <= best_score {
@@ -196,6 +306,17 @@ fn min_value(
                         }
                     }
 
+                    let bound = if best_score >= original_beta {
+                        Bound::Lower
+                    } else if best_score <= alpha {
+                        Bound::Upper
+                    } else {
+                        Bound::Exact
+                    };
+                    self.store_in_table(
+                        root_idx, best_score, bound, best_move, table,
+                    );
+
                     (best_score, best_move)
                 }
             }
@@ -203,39 +324,56 @@ fn min_value(
     }
 
     pub fn rollback(&mut self, player_idx: usize) -> Vec<Move> {
+        self.rollback_with_table(player_idx, &mut TranspositionTable::default())
+    }
+
+    // same as rollback(), but shares search results with other trees through
+    // `table`
+    pub fn rollback_with_table(
+        &mut self,
+        player_idx: usize,
+        table: &mut TranspositionTable,
+    ) -> Vec<Move> {
         match player_idx {
             0 => {
-                self.max_value(self.tree_root_idx, f64::MIN, f64::MAX);
-
-                let mut expected_moves = vec![];
-                let mut current_node_idx = self.tree_root_idx;
-                while let Some((child_idx, mv)) =
-                    self.tree[current_node_idx].best_child_node
-                {
-                    expected_moves.push(mv);
-                    current_node_idx = child_idx;
-                }
-
-                expected_moves
+                self.max_value(self.tree_root_idx, f64::MIN, f64::MAX, table);
             }
             1 => {
-                self.min_value(self.tree_root_idx, f64::MIN, f64::MAX);
-
-                let mut expected_moves = vec![];
-                let mut current_node_idx = self.tree_root_idx;
-                while let Some((child_idx, mv)) =
-                    self.tree[current_node_idx].best_child_node
-                {
-                    expected_moves.push(mv);
-                    current_node_idx = child_idx;
-                }
-
-                expected_moves
+                self.min_value(self.tree_root_idx, f64::MIN, f64::MAX, table);
             }
             _ => panic!(
                 "player index must be confined. this is a two person game"
             ),
         }
+
+        let mut expected_moves = vec![];
+        let mut current_node_idx = self.tree_root_idx;
+        while let Some((child_idx, mv)) =
+            self.tree[current_node_idx].best_child_node
+        {
+            expected_moves.push(mv);
+            current_node_idx = child_idx;
+        }
+
+        // the tree's line stops early wherever the table cut the search off,
+        // so finish it with the moves the table remembers
+        let mut state = self.tree[current_node_idx].state.clone();
+        while expected_moves.len() < self.max_depth as usize {
+            let Some(mv) = table.get(state.hash()).and_then(|e| e.best_move)
+            else {
+                break;
+            };
+            if !state
+                .current_possible_moves(state.current_turn)
+                .contains(&mv)
+            {
+                break;
+            }
+            state.make_move(mv);
+            expected_moves.push(mv);
+        }
+
+        expected_moves
     }
 
     pub fn total_subnodes(&self) -> usize {
@@ -262,7 +400,7 @@ fn create_svg_from_file(dot_file: &str, svg_file: File) {
         .arg("-Tsvg")
         .arg(dot_file)
         .stdout(Stdio::from(svg_file))
-        .spawn()
+        .status()
         .expect("failed to launch dot process");
 }
 
@@ -270,6 +408,8 @@ fn create_svg_from_file(dot_file: &str, svg_file: File) {
 pub enum SearchLimit {
     Time(Duration),
     Depth(u8),
+    // playouts for MCTS, or nodes for the alpha-beta search
+    Iterations(u32),
 }
 
 impl Default for SearchLimit {
@@ -278,121 +418,105 @@ fn default() -> Self {
     }
 }
 
-pub fn iterative_deepening(
+// `depth:<plies>`, `time:<milliseconds>` or `iterations:<count>`
+impl FromStr for SearchLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("{s} isn't a valid search limit");
+        let (kind, amount) = s.split_once(':').ok_or_else(invalid)?;
+        match kind {
+            "depth" => amount.parse().map(SearchLimit::Depth),
+            "time" => amount
+                .parse()
+                .map(|ms| SearchLimit::Time(Duration::from_millis(ms))),
+            "iterations" => amount.parse().map(SearchLimit::Iterations),
+            _ => return Err(invalid()),
+        }
+        .map_err(|_| invalid())
+    }
+}
+
+impl fmt::Display for SearchLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchLimit::Depth(depth) => write!(f, "depth:{depth}"),
+            SearchLimit::Time(time) => write!(f, "time:{}", time.as_millis()),
+            SearchLimit::Iterations(n) => write!(f, "iterations:{n}"),
+        }
+    }
+}
+
+// the GameTree version of the search: every node it visits is kept, so the
+// final tree can be drawn with svg_from_tree(). only meant for debugging,
+// since memory grows with the number of nodes
+pub fn debug_tree_search(
     state: &State,
-    time_limit: &Duration,
+    limit: &SearchLimit,
     weights: &Weights,
-) -> SuggestedMove {
+) -> (SuggestedMove, GameTree) {
     let function_beginning = Instant::now();
+    let (time_limit, max_depth, max_nodes) = match limit {
+        SearchLimit::Time(time_limit) => (Some(*time_limit), 100, usize::MAX),
+        SearchLimit::Depth(max_depth) => (None, *max_depth, usize::MAX),
+        SearchLimit::Iterations(nodes) => (None, 100, *nodes as usize),
+    };
     let mut depth_to_search = 0;
-    let mut best_move_opt: Option<Move> = None;
     let mut best_tree_opt: Option<GameTree> = None;
     let mut best_moves: Vec<Move> = vec![];
-    let mut time_building = Duration::ZERO;
-    let mut time_evaluating = Duration::ZERO;
+    // shared by every iteration, so each one starts from what the last found
+    let mut table = TranspositionTable::default();
+
+    // a depth limit builds a single tree of that depth
+    if let SearchLimit::Depth(_) = limit {
+        depth_to_search = max_depth - 1;
+    }
 
-    while Instant::now().duration_since(function_beginning) < *time_limit
-        && depth_to_search < 100
+    while time_limit.is_none_or(|t| function_beginning.elapsed() < t)
+        && depth_to_search < max_depth
+        && best_tree_opt
+            .as_ref()
+            .is_none_or(|tree| tree.total_subnodes() < max_nodes)
     {
         depth_to_search += 1;
+        table.new_search();
 
-        let before_building_tree = Instant::now();
         let mut tree =
             GameTree::new(state.to_owned(), depth_to_search, weights);
         tree.build_eval_tree();
-        let after_building_tree = Instant::now();
-        best_moves = tree.rollback(state.current_turn as usize);
-        let m = best_moves
-            .first()
-            .expect("Rollback did not return a potential move")
-            .to_owned();
-        let after_evaluating_tree = Instant::now();
-
-        best_move_opt = Some(m);
+        best_moves =
+            tree.rollback_with_table(state.current_turn as usize, &mut table);
         best_tree_opt = Some(tree);
-        time_building +=
-            after_building_tree.duration_since(before_building_tree);
-        time_evaluating +=
-            after_evaluating_tree.duration_since(after_building_tree);
     }
 
-    let best_move = best_move_opt.expect("could not find a best move in time");
     let best_tree = best_tree_opt.expect("could not find a best tree in time");
+    let best_move = *best_moves
+        .first()
+        .expect("Rollback did not return a potential move");
 
-    SuggestedMove {
+    let suggestion = SuggestedMove {
         suggestion: best_move,
         max_depth_considered: depth_to_search,
-        time_building_trees: time_building,
-        time_evaluating_trees: time_evaluating,
+        time_searching: function_beginning.elapsed(),
         total_nodes_considered: best_tree.total_subnodes(),
-        heuristical_reasoning: best_tree
-            .weights
-            .new_with_state_and_moves(state.clone(), &best_moves),
+        reasoning: best_tree.weights.explain(state, &best_moves),
         expected_line: best_moves,
-    }
+        table_hits: table.hits(),
+        table_misses: table.misses(),
+    };
+    (suggestion, best_tree)
 }
 
 pub fn get_best_move(
     state: &State,
     limit: &SearchLimit,
     weights: &Weights,
+    config: &SearchConfig,
 ) -> SuggestedMove {
-    match limit {
-        SearchLimit::Time(time_limit) => {
-            iterative_deepening(state, time_limit, weights)
-        }
-        SearchLimit::Depth(max_depth) => {
-            let before_building_tree = Instant::now();
-            let mut tree = GameTree::new(state.to_owned(), *max_depth, weights);
-            tree.build_eval_tree();
-            let after_building_tree = Instant::now();
-            let moves = tree.rollback(state.current_turn as usize);
-            let m = moves
-                .first()
-                .expect("Rollback did not return a potential move")
-                .to_owned();
-            let after_evaluating_tree = Instant::now();
-
-            SuggestedMove {
-                suggestion: m,
-                max_depth_considered: max_depth.to_owned(),
-                time_building_trees: after_building_tree
-                    .duration_since(before_building_tree),
-                time_evaluating_trees: after_evaluating_tree
-                    .duration_since(after_building_tree),
-                total_nodes_considered: tree.total_subnodes(),
-                heuristical_reasoning: {
-                    tree.weights.new_with_state_and_moves(state.clone(), &moves)
-                },
-                expected_line: moves,
-            }
-        }
-    }
-}
-
-pub struct SuggestedMove {
-    pub suggestion: Move,
-    max_depth_considered: u8,
-    time_building_trees: Duration,
-    time_evaluating_trees: Duration,
-    total_nodes_considered: usize,
-    heuristical_reasoning: HeuristicWeightsWithTwoStates,
-    expected_line: Vec<Move>,
-}
-
-impl Debug for SuggestedMove {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!(
-            "AI suggested {:?} ({} nodes considered, depth of {}) in {:.2} seconds ({:.2} to build, {:.2} to evaluate) with reasoning: {:#?}. Expected the sequence: {:?}",
-            self.suggestion,
-            self.total_nodes_considered.separated_string(),
-            self.max_depth_considered,
-            (self.time_building_trees + self.time_evaluating_trees)
-                .as_secs_f32(),
-            self.time_building_trees.as_secs_f32(),
-            self.time_evaluating_trees.as_secs_f32(),
-            self.heuristical_reasoning,
-            self.expected_line
-        ))
+    if *DEBUG_TREE {
+        let (suggestion, tree) = debug_tree_search(state, limit, weights);
+        tree.svg_from_tree();
+        return suggestion;
     }
+    Searcher::with_config(weights, *config).search(state, limit)
 }
@@ -1,17 +1,45 @@
+use crate::ai::book::{
+    BookSelection, OpeningBook, DEFAULT_MAX_PLIES, DEFAULT_MIN_GAMES,
+};
+use crate::ai::export::{self, ExportOptions};
 use crate::ai::heuristics::NUM_HEURISTICS;
+use crate::ai::mcts::Rollout;
+use crate::ai::tablebase::{Tablebase, DEFAULT_MAX_PIECES};
+use crate::ai::training::{load_samples, train, TrainConfig};
 use crate::ai::tree::SearchLimit;
 use crate::game::gamestate::{GameBuilder, State};
+use crate::game::perft::{divide, perft, PerftCounts};
 
 use crate::genetic::mutate_from_recovery;
+use crate::genetic::{Batch, Experiment, DEFAULT_EXPERIMENT};
+use crate::genetic::{CmaEs, ExperimentConfig, Genetic, Optimizer};
+use crate::genetic::{Noise, Referee, SelfPlay};
+use crate::genetic::{OptimizerKind, DEFAULT_AGENT_LIMIT};
 use crate::{genetic, DATABASE_URL};
 
-use crate::game::player::{PossiblePlayer, NN};
+use crate::game::player::{NNSearch, PossiblePlayer, MCTS, NN};
 
 use crate::game::player::{Person, AI};
+use lazy_static::lazy_static;
 use log::{debug, info};
 use rusqlite::Connection;
 use std::{env, io};
 
+lazy_static! {
+    static ref NN_MODEL_PATH: String = match env::var("NN_MODEL_PATH") {
+        Ok(path) => {
+            info!("Using NN_MODEL_PATH environment variable ({})", path);
+            path
+        }
+        Err(_) => "neuralnet/exp2.joblib".to_string(),
+    };
+    static ref EXPERIMENT_CONFIG: Option<String> =
+        env::var("EXPERIMENT_CONFIG").ok().map(|path| {
+            info!("Using EXPERIMENT_CONFIG environment variable ({})", path);
+            path
+        });
+}
+
 #[derive(PartialEq)]
 enum GameType {
     Black,
@@ -23,6 +51,7 @@ enum GameType {
     BlackWeights,
     WhiteWeights,
     BlackNN,
+    AIvsMCTS,
 }
 
 fn get_gametype_from_user() -> GameType {
@@ -35,6 +64,7 @@ fn get_gametype_from_user() -> GameType {
                \n\t(6) to play as white vs a black AI with inputted weights, or\
                \n\t(7) to play as black vs a white AI with inputted weights, or\
                \n\t(8) to play as white vs a black NN, or\
+               \n\t(9) to have the AI play a match against MCTS, or\
                \n\t(0) to launch the genetic algorithm."
     );
 
@@ -65,6 +95,7 @@ fn get_gametype_from_string(g: &str) -> Option<GameType> {
         "6" => Some(GameType::BlackWeights),
         "7" => Some(GameType::WhiteWeights),
         "8" => Some(GameType::BlackNN),
+        "9" => Some(GameType::AIvsMCTS),
         "0" => Some(GameType::Genetic),
         _ => None,
     }
@@ -167,7 +198,7 @@ fn get_game_from_gametype(game_type: GameType) -> State {
 
             gb.set_player_1(PossiblePlayer::NN(NN::new(
                 "NN".to_string(),
-                "neuralnet/exp2.joblib".to_string(),
+                NN_MODEL_PATH.to_string(),
             )))
             .set_player_2(PossiblePlayer::Person(Person::new(player_name)))
             .build()
@@ -181,72 +212,337 @@ fn get_game_from_gametype(game_type: GameType) -> State {
 pub fn play_game(game: &mut State) {
     debug!("{game}");
 
+    // the moves played so far, for people to take theirs back
+    let mut undos = vec![];
     while game.active {
-        game.play_one_turn();
+        match game.players[game.current_turn as usize].clone() {
+            PossiblePlayer::Person(person) => {
+                person.play_turn(game, &mut undos)
+            }
+            _ => game.play_one_turn(),
+        }
+        undos.extend(game.last_move.take());
         debug!("{game}");
         // game.add_to_state_history();
     }
     // game.push_game_and_state().unwrap();
 }
 
-pub fn start_genetic_process() {
-    let conn = Connection::open(&*DATABASE_URL).unwrap();
-    let mut stmt = conn
-        .prepare(
-            r#"
-            SELECT batch_id, agents FROM recovery_table
-            ORDER BY batch_id DESC LIMIT 1
-            "#,
-        )
-        .unwrap();
-    let mut batch_agents_iter = stmt
-        .query_map([], |row| {
-            let batch_num: u32 = row.get(0).unwrap();
-            let bin_agent: Vec<u8> = row.get(1).unwrap();
-            let mut agents_and_scores: Vec<(AI, i16)> =
-                bincode::deserialize(&bin_agent).unwrap();
-            agents_and_scores.sort_by_key(|(_, elo)| -elo);
-            let agents: Vec<AI> =
-                agents_and_scores.into_iter().map(|e| e.0).collect();
-            Ok((batch_num, agents))
-        })
-        .unwrap()
-        .peekable();
+// with_book() has an AI play the openings of the opening book, if there is
+// one (and BOOK_SELECTION turns it on), which is only for interactive games
+// and self-play, never for ones that measure its strength
+fn with_book(player: PossiblePlayer) -> PossiblePlayer {
+    match player {
+        PossiblePlayer::AI(ai) => PossiblePlayer::AI(
+            ai.with_book_selection(BookSelection::from_env()),
+        ),
+        player => player,
+    }
+}
 
-    let ai = if batch_agents_iter.peek().is_none() {
-        // if no rows exist in recovery_table, start from scratch
-        info!("No rows found in the recovery table, initializing genetic algorithm...");
-        genetic::run(1, None)
-    } else {
-        // if rows exist, start from most recent agents
-        let found_batch = batch_agents_iter.next().unwrap();
-        let (batch_num, agents) = found_batch.unwrap();
+// experiment_config() reads the config of the genetic process from the file
+// after `--config` (or EXPERIMENT_CONFIG), and then applies the overrides
+// among `args`
+pub fn experiment_config(args: &[String]) -> ExperimentConfig {
+    let path = match args.iter().position(|arg| arg == "--config") {
+        Some(i) => Some(args.get(i + 1).expect("the config path is missing")),
+        None => EXPERIMENT_CONFIG.as_ref(),
+    };
+    let config = match path {
+        Some(path) => ExperimentConfig::load(path).unwrap_or_else(|e| {
+            panic!("couldn't load the experiment config at {path} ({e})")
+        }),
+        None => ExperimentConfig::default(),
+    };
+    with_overrides(config, args)
+}
 
-        // drop to free the database connection before running genetic
-        drop(batch_agents_iter);
+// with_overrides() applies the overrides among `args` to `config`:
+// `<field>=<value>`, or the optimizer's name on its own (other than the
+// values of `--` flags)
+fn with_overrides(
+    mut config: ExperimentConfig,
+    args: &[String],
+) -> ExperimentConfig {
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 && args[i - 1].starts_with("--") {
+            continue;
+        }
+        if let Some((key, value)) = arg.split_once('=') {
+            config.set(key, value).unwrap_or_else(|e| panic!("{e}"));
+        } else if let Ok(kind) = arg.parse::<OptimizerKind>() {
+            config.optimizer = kind;
+        }
+    }
+    if let Err(e) = config.validate() {
+        panic!("invalid experiment config ({e})");
+    }
+    config
+}
 
-        info!(
-            "Found existing rows in recovery table for gen {batch_num}, \
-             mutating & starting genetic algorithm from {}",
-            batch_num + 1
-        );
+// run_experiment() runs the experiment after `--name` (or the default one).
+// new experiments start with the config from experiment_config(), and ones
+// that exist carry on with their own, with the overrides among `args`
+pub fn run_experiment(args: &[String]) {
+    let name = match args.iter().position(|arg| arg == "--name") {
+        Some(i) => args.get(i + 1).expect("the experiment's name is missing"),
+        None => DEFAULT_EXPERIMENT,
+    };
 
-        genetic::run(
-            batch_num + 1,
-            Some(mutate_from_recovery(batch_num, agents)),
-        )
+    let conn = Connection::open(&*DATABASE_URL).unwrap();
+    let experiment = match Experiment::find(&conn, name).unwrap() {
+        Some(experiment) => {
+            if args.iter().any(|arg| arg == "--config") {
+                panic!(
+                    "{name} already has a config, override fields of it \
+                     instead of passing --config"
+                );
+            }
+            experiment
+        }
+        None => {
+            info!("Starting the new experiment {name}");
+            Experiment::create(&conn, name, &experiment_config(args)).unwrap()
+        }
+    };
+    drop(conn);
+    let config = with_overrides(experiment.config.clone(), args);
+    start_genetic_process(&experiment, config)
+}
+
+// start_genetic_process() runs the optimizer for `experiment`, carrying on
+// from its last batch if there is one
+pub fn start_genetic_process(
+    experiment: &Experiment,
+    config: ExperimentConfig,
+) {
+    let conn = Connection::open(&*DATABASE_URL).unwrap();
+    let last_batch = experiment.last_batch(&conn).unwrap();
+    // drop to free the database connection before running genetic
+    drop(conn);
+
+    info!(
+        "Optimizing the weights for experiment {} with {config:?}",
+        experiment.name
+    );
+    let ai = match last_batch {
+        None => {
+            // if the experiment has no batches yet, start from scratch
+            info!("No batches found for the experiment, initializing genetic algorithm...");
+            let optimizer: Box<dyn Optimizer> = match config.optimizer {
+                OptimizerKind::Genetic => {
+                    Box::new(Genetic::new(1, None, &config))
+                }
+                OptimizerKind::CmaEs => Box::new(CmaEs::from_scratch(&config)),
+            };
+            genetic::run(experiment.id, 1, optimizer, &config)
+        }
+        Some(Batch {
+            batch_num,
+            agents,
+            checkpoint,
+        }) => {
+            // if it has, start from its most recent agents
+            info!(
+                "Found batch {batch_num} of the experiment, mutating & \
+                 starting genetic algorithm from {}",
+                batch_num + 1
+            );
+
+            let optimizer: Box<dyn Optimizer> = match config.optimizer {
+                OptimizerKind::Genetic => Box::new(Genetic::new(
+                    batch_num + 1,
+                    Some(mutate_from_recovery(batch_num, agents, &config)),
+                    &config,
+                )),
+                // batches left by the genetic algorithm have no CMA-ES state,
+                // so the search is centered on their best agent
+                OptimizerKind::CmaEs => Box::new(
+                    CmaEs::from_checkpoint(&checkpoint, &config)
+                        .unwrap_or_else(|| {
+                            CmaEs::new(agents[0].weights, &config)
+                        }),
+                ),
+            };
+            genetic::run(experiment.id, batch_num + 1, optimizer, &config)
+        }
     };
     info!("Genetic process completed");
 
     if env::var("PLAY_AFTER").is_ok() {
         let mut g = GameBuilder::new()
             .set_player_1(PossiblePlayer::Person(Person::default()))
-            .set_player_2(PossiblePlayer::AI(ai))
+            .set_player_2(with_book(PossiblePlayer::AI(ai)))
             .build();
         play_game(&mut g);
     }
 }
 
+// runs the subcommand named by the program's arguments, if there is one
+// (any other argument starts the genetic process)
+pub fn run_command(args: &[String]) -> bool {
+    match args.first().map(String::as_str) {
+        Some("tablebase") => generate_tablebase(&args[1..]),
+        Some("book") => build_opening_book(&args[1..]),
+        Some("nn-vs-ai") => play_nn_search_vs_ai(&args[1..]),
+        Some("train") => train_nn(&args[1..]),
+        Some("selfplay") => generate_self_play(&args[1..]),
+        Some("export") => export_positions(&args[1..]),
+        Some("perft") => run_perft(&args[1..]),
+        Some("experiment") => manage_experiments(&args[1..]),
+        _ => return false,
+    }
+    true
+}
+
+// `tablebase [max pieces per side] [path]`
+fn generate_tablebase(args: &[String]) {
+    let max_pieces = args
+        .first()
+        .map(|n| n.parse().expect("max pieces should be a number"))
+        .unwrap_or(DEFAULT_MAX_PIECES);
+    let path = args.get(1).map_or("tablebase.bin", String::as_str);
+
+    info!("Generating a tablebase for up to {max_pieces} pieces per side");
+    let tablebase = Tablebase::generate(max_pieces);
+    tablebase.save(path).unwrap();
+    println!(
+        "Saved the tablebase to {path}, set TABLEBASE_PATH={path} to use it"
+    );
+}
+
+// `book [max plies] [path]`, from the games at DATABASE_URL
+fn build_opening_book(args: &[String]) {
+    let max_plies = args
+        .first()
+        .map(|n| n.parse().expect("max plies should be a number"))
+        .unwrap_or(DEFAULT_MAX_PLIES);
+    let path = args.get(1).map_or("book.bin", String::as_str);
+
+    let conn = Connection::open(&*DATABASE_URL).unwrap();
+    let book = OpeningBook::build(&conn, max_plies, DEFAULT_MIN_GAMES).unwrap();
+    book.save(path).unwrap();
+    println!(
+        "Saved {} positions to {path}, set OPENING_BOOK_PATH={path} to use it",
+        book.len()
+    );
+}
+
+// `perft <depth> [repr string] [--divide]` counts the positions `depth`
+// plies from the start (or the given position), optionally per first move
+fn run_perft(args: &[String]) {
+    let split = args.iter().any(|arg| arg == "--divide");
+    let args: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+    let depth = args
+        .first()
+        .expect("the depth is missing")
+        .parse()
+        .expect("depth should be a number");
+    let mut state = match args.get(1) {
+        Some(repr) => State::from_repr_string(repr)
+            .expect("couldn't read the position's repr string"),
+        None => GameBuilder::new().build(),
+    };
+
+    // the total is added up from the divide rather than searched again (a
+    // divide at depth 0 has no moves, so it falls back to perft)
+    let counts = match split && depth > 0 {
+        true => {
+            let mut total = PerftCounts::default();
+            for (m, counts) in divide(&mut state, depth) {
+                println!(
+                    "{m:?}: {} nodes, {} captures, {} wins",
+                    counts.nodes, counts.captures, counts.wins
+                );
+                total += counts;
+            }
+            total
+        }
+        false => perft(&mut state, depth),
+    };
+    println!(
+        "perft({depth}) = {} nodes, {} captures, {} wins",
+        counts.nodes, counts.captures, counts.wins
+    );
+}
+
+// `experiment list`, `experiment resume <name> [overrides]`,
+// `experiment fork <parent> <name> [overrides]` or `experiment delete <name>`,
+// where the overrides are applied to the experiment's config
+fn manage_experiments(args: &[String]) {
+    let mut conn = Connection::open(&*DATABASE_URL).unwrap();
+    let find = |name: Option<&String>| {
+        let name = name.expect("the experiment's name is missing");
+        Experiment::find(&conn, name)
+            .unwrap()
+            .unwrap_or_else(|| panic!("there's no experiment called {name}"))
+    };
+
+    match args.first().map(String::as_str) {
+        Some("list") => {
+            let experiments = Experiment::list(&conn).unwrap();
+            for experiment in &experiments {
+                let parent = experiments
+                    .iter()
+                    .find(|parent| Some(parent.id) == experiment.parent_id)
+                    .map_or("-", |parent| parent.name.as_str());
+                println!(
+                    "{}: {} batches with {}, forked from {parent}, code {}, \
+                     created {}",
+                    experiment.name,
+                    experiment.num_batches(&conn).unwrap(),
+                    experiment.config.optimizer,
+                    experiment.code_version.as_deref().unwrap_or("unknown"),
+                    experiment.created
+                );
+            }
+        }
+        Some("resume") => {
+            let experiment = find(args.get(1));
+            let config = with_overrides(experiment.config.clone(), &args[2..]);
+            drop(conn);
+            start_genetic_process(&experiment, config)
+        }
+        Some("fork") => {
+            let parent = find(args.get(1));
+            let name = args.get(2).expect("the fork's name is missing");
+            let config = with_overrides(parent.config.clone(), &args[3..]);
+            parent.fork(&conn, name, &config).unwrap();
+            println!(
+                "Forked {name} from {} after {} batches, resume it to run it",
+                parent.name,
+                parent.num_batches(&conn).unwrap()
+            );
+        }
+        Some("delete") => {
+            let experiment = find(args.get(1));
+            let forks: Vec<String> = Experiment::list(&conn)
+                .unwrap()
+                .into_iter()
+                .filter(|fork| fork.parent_id == Some(experiment.id))
+                .map(|fork| fork.name)
+                .collect();
+            if !forks.is_empty() {
+                panic!(
+                    "{} was forked into {}, delete those first",
+                    experiment.name,
+                    forks.join(", ")
+                );
+            }
+            let name = experiment.name.clone();
+            experiment.delete(&mut conn).unwrap();
+            println!("Deleted {name} with its batches and games");
+        }
+        _ => panic!(
+            "the experiment command should be list, resume, fork or delete"
+        ),
+    }
+}
+
 pub fn choose_phase() {
     let gametype = match env::var("LAUNCH_ARG") {
         Ok(i) => {
@@ -260,10 +556,194 @@ pub fn choose_phase() {
     };
 
     if gametype == GameType::Genetic {
-        start_genetic_process()
+        run_experiment(&[])
+    } else if gametype == GameType::AIvsMCTS {
+        play_ai_vs_mcts()
     } else {
-        play_game(&mut get_game_from_gametype(gametype))
+        let mut game = get_game_from_gametype(gametype);
+        game.players = game.players.clone().map(with_book);
+        play_game(&mut game)
+    }
+}
+
+// plays one game with each side as black, between the heuristic AI and an
+// MCTS player given the same time
+pub fn play_ai_vs_mcts() {
+    let ai = PossiblePlayer::AI(AI::from_name("AI".to_string()));
+    let mcts = PossiblePlayer::MCTS(MCTS::new(
+        "MCTS".to_string(),
+        [1.0; NUM_HEURISTICS],
+        SearchLimit::default(),
+        Rollout::Heuristic,
+    ));
+
+    let (ai_wins, mcts_wins) = Referee::play_one_match(&ai, &mcts);
+    println!("The AI won {ai_wins} game(s), MCTS won {mcts_wins}");
+}
+
+// `selfplay <dataset> [games] [player one] [player two] [noise] [noise plies]`
+// plays games between the two players (switching colors every game) and
+// saves them to the database under the dataset tag
+fn generate_self_play(args: &[String]) {
+    let dataset = args.first().expect("the dataset tag is missing");
+    let games = args
+        .get(1)
+        .map_or(100, |n| n.parse().expect("games should be a number"));
+    let players = [
+        player_from_spec(args.get(2).map_or("ai", String::as_str)),
+        player_from_spec(args.get(3).map_or("ai", String::as_str)),
+    ]
+    .map(with_book);
+    let noise =
+        noise_from_spec(args.get(4).map_or("epsilon:0.1", String::as_str));
+    let noise_plies = args.get(5).map_or(usize::MAX, |n| {
+        n.parse().expect("noise plies should be a number")
+    });
+
+    let mut conn = Connection::open(&*DATABASE_URL).unwrap();
+    let black_wins = SelfPlay::new(players, games, dataset.to_string())
+        .with_noise(noise, noise_plies)
+        .run(&mut conn)
+        .unwrap();
+    println!(
+        "Saved {games} games to dataset {dataset} (black won {black_wins})"
+    );
+}
+
+// `ai[:depth]`, `mcts[:iterations]`, `nn:<model path>` or
+// `nnsearch:<model JSON path>[:depth]`
+fn player_from_spec(spec: &str) -> PossiblePlayer {
+    let parts: Vec<&str> = spec.split(':').collect();
+    let depth = |part: Option<&&str>| {
+        part.map_or(DEFAULT_AGENT_LIMIT, |depth| {
+            SearchLimit::Depth(depth.parse().expect("depth should be a number"))
+        })
+    };
+    match parts[..] {
+        ["ai", ..] => PossiblePlayer::AI(AI::new(
+            spec.to_string(),
+            [1.0; NUM_HEURISTICS],
+            depth(parts.get(1)),
+        )),
+        ["mcts", ..] => PossiblePlayer::MCTS(MCTS::new(
+            spec.to_string(),
+            [1.0; NUM_HEURISTICS],
+            parts.get(1).map_or(SearchLimit::default(), |iterations| {
+                SearchLimit::Iterations(
+                    iterations.parse().expect("iterations should be a number"),
+                )
+            }),
+            Rollout::Heuristic,
+        )),
+        ["nn", path] => {
+            PossiblePlayer::NN(NN::new(spec.to_string(), path.to_string()))
+        }
+        ["nnsearch", path, ..] => PossiblePlayer::NNSearch(NNSearch::new(
+            spec.to_string(),
+            path.to_string(),
+            depth(parts.get(2)),
+        )),
+        _ => panic!("couldn't recognize the player {spec}"),
+    }
+}
+
+// `none`, `epsilon:<chance>` or `temperature:<temperature>`
+fn noise_from_spec(spec: &str) -> Noise {
+    spec.parse().unwrap_or_else(|e| panic!("{e}"))
+}
+
+// `train [path] [epochs] [dataset]` fits a network to the games at
+// DATABASE_URL (all of them unless a dataset is given) and saves it where
+// `NN` can load it natively
+fn train_nn(args: &[String]) {
+    let path = args.first().map_or("neuralnet/model.json", String::as_str);
+    let mut config = TrainConfig::default();
+    if let Some(epochs) = args.get(1) {
+        config.epochs = epochs.parse().expect("epochs should be a number");
+    }
+
+    let conn = Connection::open(&*DATABASE_URL).unwrap();
+    let dataset = args.get(2).map(String::as_str);
+    let samples = load_samples(&conn, dataset).unwrap();
+    info!("Training on {} positions with {config:?}", samples.len());
+
+    let (model, losses) = train(&samples, &config, &mut rand::thread_rng());
+    for (epoch, loss) in losses.iter().enumerate() {
+        println!(
+            "Epoch {}: train loss {:.5}, validation loss {:.5}",
+            epoch + 1,
+            loss.train,
+            loss.validation
+        );
     }
+    model.save(path).unwrap();
+    println!("Saved the network with the lowest validation loss to {path}");
+}
+
+// `export <path prefix> [csv|npy] [test fraction] [dataset] [--heuristics]
+// [--mirror]` writes the positions at DATABASE_URL, split by game, to
+// `<prefix>_train.<format>` and `<prefix>_test.<format>`
+fn export_positions(args: &[String]) {
+    let options = ExportOptions {
+        heuristics: args.iter().any(|arg| arg == "--heuristics"),
+        mirror: args.iter().any(|arg| arg == "--mirror"),
+    };
+    let args: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+    let prefix = args.first().expect("the output path prefix is missing");
+    let format = args.get(1).copied().unwrap_or("csv");
+    let test_fraction = args.get(2).map_or(0.2, |f| {
+        f.parse().expect("the test fraction should be a number")
+    });
+    let dataset = args.get(3).copied();
+
+    let conn = Connection::open(&*DATABASE_URL).unwrap();
+    let (train, test) = export::load_split(
+        &conn,
+        dataset,
+        test_fraction,
+        options,
+        &mut rand::thread_rng(),
+    )
+    .unwrap();
+
+    for (split, rows) in [("train", &train), ("test", &test)] {
+        let path = format!("{prefix}_{split}.{format}");
+        match format {
+            "csv" => export::write_csv(&path, rows, options),
+            "npy" => export::write_npy(&path, rows, options),
+            _ => panic!("unknown export format {format} (csv or npy)"),
+        }
+        .unwrap();
+        println!("Saved {} {split} positions to {path}", rows.len());
+    }
+}
+
+// `nn-vs-ai <model path> [depth]` plays one game with each side as black,
+// between the heuristic AI and a search guided by the network, both
+// searching to the same depth
+fn play_nn_search_vs_ai(args: &[String]) {
+    let path = args.first().expect("the NN model path is missing");
+    let limit = args.get(1).map_or(DEFAULT_AGENT_LIMIT, |depth| {
+        SearchLimit::Depth(depth.parse().expect("depth should be a number"))
+    });
+
+    let nn = PossiblePlayer::NNSearch(NNSearch::new(
+        "NN search".to_string(),
+        path.to_string(),
+        limit,
+    ));
+    let ai = PossiblePlayer::AI(AI::new(
+        "AI".to_string(),
+        [1.0; NUM_HEURISTICS],
+        limit,
+    ));
+
+    let (nn_wins, ai_wins) = Referee::play_one_match(&nn, &ai);
+    println!("The NN search won {nn_wins} game(s), the AI won {ai_wins}");
 }
 
 pub fn get_name_from_user(label: &str) -> String {
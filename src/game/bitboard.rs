@@ -0,0 +1,158 @@
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+
+/// the number of hexes on a milestone board
+pub const NUM_HEXES: usize = 37;
+
+/// a set of hexes, where bit `i` is set if hex `i` is part of the set
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const FULL: Bitboard = Bitboard((1 << NUM_HEXES) - 1);
+
+    pub const fn from_hexes(hexes: &[usize]) -> Bitboard {
+        let mut mask = 0;
+        let mut i = 0;
+        while i < hexes.len() {
+            mask |= 1 << hexes[i];
+            i += 1;
+        }
+        Bitboard(mask)
+    }
+
+    pub const fn from_hex(hex: usize) -> Bitboard {
+        Bitboard(1 << hex)
+    }
+
+    pub const fn contains(self, hex: usize) -> bool {
+        self.0 & (1 << hex) != 0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn count(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn insert(&mut self, hex: usize) {
+        self.0 |= 1 << hex;
+    }
+
+    pub fn remove(&mut self, hex: usize) {
+        self.0 &= !(1 << hex);
+    }
+
+    // the lowest-indexed hex in the set (closest to black's home)
+    pub const fn first(self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(self.0.trailing_zeros() as usize)
+        }
+    }
+
+    // the highest-indexed hex in the set (closest to white's home)
+    pub const fn last(self) -> Option<usize> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(63 - self.0.leading_zeros() as usize)
+        }
+    }
+
+    pub fn iter(self) -> Hexes {
+        Hexes(self.0)
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = usize;
+    type IntoIter = Hexes;
+
+    fn into_iter(self) -> Hexes {
+        self.iter()
+    }
+}
+
+/// iterates over the hexes of a `Bitboard` in ascending order
+#[derive(Clone, Copy)]
+pub struct Hexes(u64);
+
+impl Iterator for Hexes {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let hex = self.0.trailing_zeros() as usize;
+        // clear the lowest set bit
+        self.0 &= self.0 - 1;
+        Some(hex)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.0.count_ones() as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Hexes {}
+
+impl DoubleEndedIterator for Hexes {
+    fn next_back(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let hex = 63 - self.0.leading_zeros() as usize;
+        self.0 &= !(1 << hex);
+        Some(hex)
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Bitboard) {
+        self.0 &= rhs.0
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Bitboard) {
+        self.0 |= rhs.0
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    // only the 37 hexes of the board are kept
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0 & Bitboard::FULL.0)
+    }
+}
+
+impl fmt::Debug for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
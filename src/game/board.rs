@@ -1,11 +1,17 @@
+use super::bitboard::{Bitboard, NUM_HEXES};
 use super::pieces::Piece;
-use lazy_static::lazy_static;
+use super::zobrist::PIECE_KEYS;
 
 use std::fmt;
+use std::ops::{Deref, DerefMut};
 
-#[derive(Clone, Copy)]
+/// a milestone board, stored as one bitboard per color
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Board {
-    pub board: [Hole; 37],
+    // indexed by turn (black is 0, white is 1)
+    pieces: [Bitboard; 2],
+    // zobrist hash of the piece placement, kept up to date on every change
+    hash: u64,
 }
 
 impl fmt::Display for Board {
@@ -17,119 +23,119 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 
         // first line
         repr.push_str(&space.repeat(3));
-        repr.push_str(&format!("{:?}", self.board[0]));
+        repr.push_str(&format!("{:?}", self.hole(0)));
         repr.push_str(&space.repeat(3));
         repr.push('\n');
 
         // second line
         repr.push_str(&space.repeat(2));
-        repr.push_str(&format!("{:?}", self.board[1]));
+        repr.push_str(&format!("{:?}", self.hole(1)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[2]));
+        repr.push_str(&format!("{:?}", self.hole(2)));
         repr.push_str(&space.repeat(2));
         repr.push('\n');
 
         // third line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[3]));
+        repr.push_str(&format!("{:?}", self.hole(3)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[4]));
+        repr.push_str(&format!("{:?}", self.hole(4)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[5]));
+        repr.push_str(&format!("{:?}", self.hole(5)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // fourth line
-        repr.push_str(&format!("{:?}", self.board[6]));
+        repr.push_str(&format!("{:?}", self.hole(6)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[7]));
+        repr.push_str(&format!("{:?}", self.hole(7)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[8]));
+        repr.push_str(&format!("{:?}", self.hole(8)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[9]));
+        repr.push_str(&format!("{:?}", self.hole(9)));
         repr.push('\n');
 
         // fifth line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[10]));
+        repr.push_str(&format!("{:?}", self.hole(10)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[11]));
+        repr.push_str(&format!("{:?}", self.hole(11)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[12]));
+        repr.push_str(&format!("{:?}", self.hole(12)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // sixth line
-        repr.push_str(&format!("{:?}", self.board[13]));
+        repr.push_str(&format!("{:?}", self.hole(13)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[14]));
+        repr.push_str(&format!("{:?}", self.hole(14)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[15]));
+        repr.push_str(&format!("{:?}", self.hole(15)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[16]));
+        repr.push_str(&format!("{:?}", self.hole(16)));
         repr.push('\n');
 
         // seventh line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[17]));
+        repr.push_str(&format!("{:?}", self.hole(17)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[18]));
+        repr.push_str(&format!("{:?}", self.hole(18)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[19]));
+        repr.push_str(&format!("{:?}", self.hole(19)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // eigth line
-        repr.push_str(&format!("{:?}", self.board[20]));
+        repr.push_str(&format!("{:?}", self.hole(20)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[21]));
+        repr.push_str(&format!("{:?}", self.hole(21)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[22]));
+        repr.push_str(&format!("{:?}", self.hole(22)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[23]));
+        repr.push_str(&format!("{:?}", self.hole(23)));
         repr.push('\n');
 
         // ninth line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[24]));
+        repr.push_str(&format!("{:?}", self.hole(24)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[25]));
+        repr.push_str(&format!("{:?}", self.hole(25)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[26]));
+        repr.push_str(&format!("{:?}", self.hole(26)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // tenth line
-        repr.push_str(&format!("{:?}", self.board[27]));
+        repr.push_str(&format!("{:?}", self.hole(27)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[28]));
+        repr.push_str(&format!("{:?}", self.hole(28)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[29]));
+        repr.push_str(&format!("{:?}", self.hole(29)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[30]));
+        repr.push_str(&format!("{:?}", self.hole(30)));
         repr.push('\n');
 
         // eleventh line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[31]));
+        repr.push_str(&format!("{:?}", self.hole(31)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[32]));
+        repr.push_str(&format!("{:?}", self.hole(32)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[33]));
+        repr.push_str(&format!("{:?}", self.hole(33)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // twelfth line
         repr.push_str(&space.repeat(2));
-        repr.push_str(&format!("{:?}", self.board[34]));
+        repr.push_str(&format!("{:?}", self.hole(34)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("{:?}", self.board[35]));
+        repr.push_str(&format!("{:?}", self.hole(35)));
         repr.push_str(&space.repeat(2));
         repr.push('\n');
 
         // thirteenth line
         repr.push_str(&space.repeat(3));
-        repr.push_str(&format!("{:?}", self.board[36]));
+        repr.push_str(&format!("{:?}", self.hole(36)));
         repr.push_str(&space.repeat(3));
         repr.push('\n');
 
@@ -148,119 +154,119 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 
         // first line
         repr.push_str(&space.repeat(3));
-        repr.push_str(&format!(" 0:{:?}", self.board[0]));
+        repr.push_str(&format!(" 0:{:?}", self.hole(0)));
         repr.push_str(&space.repeat(3));
         repr.push('\n');
 
         // second line
         repr.push_str(&space.repeat(2));
-        repr.push_str(&format!(" 1:{:?}", self.board[1]));
+        repr.push_str(&format!(" 1:{:?}", self.hole(1)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!(" 2:{:?}", self.board[2]));
+        repr.push_str(&format!(" 2:{:?}", self.hole(2)));
         repr.push_str(&space.repeat(2));
         repr.push('\n');
 
         // third line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!(" 3:{:?}", self.board[3]));
+        repr.push_str(&format!(" 3:{:?}", self.hole(3)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!(" 4:{:?}", self.board[4]));
+        repr.push_str(&format!(" 4:{:?}", self.hole(4)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!(" 5:{:?}", self.board[5]));
+        repr.push_str(&format!(" 5:{:?}", self.hole(5)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // fourth line
-        repr.push_str(&format!(" 6:{:?}", self.board[6]));
+        repr.push_str(&format!(" 6:{:?}", self.hole(6)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!(" 7:{:?}", self.board[7]));
+        repr.push_str(&format!(" 7:{:?}", self.hole(7)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!(" 8:{:?}", self.board[8]));
+        repr.push_str(&format!(" 8:{:?}", self.hole(8)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!(" 9:{:?}", self.board[9]));
+        repr.push_str(&format!(" 9:{:?}", self.hole(9)));
         repr.push('\n');
 
         // fifth line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("10:{:?}", self.board[10]));
+        repr.push_str(&format!("10:{:?}", self.hole(10)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("11:{:?}", self.board[11]));
+        repr.push_str(&format!("11:{:?}", self.hole(11)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("12:{:?}", self.board[12]));
+        repr.push_str(&format!("12:{:?}", self.hole(12)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // sixth line
-        repr.push_str(&format!("13:{:?}", self.board[13]));
+        repr.push_str(&format!("13:{:?}", self.hole(13)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("14:{:?}", self.board[14]));
+        repr.push_str(&format!("14:{:?}", self.hole(14)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("15:{:?}", self.board[15]));
+        repr.push_str(&format!("15:{:?}", self.hole(15)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("16:{:?}", self.board[16]));
+        repr.push_str(&format!("16:{:?}", self.hole(16)));
         repr.push('\n');
 
         // seventh line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("17:{:?}", self.board[17]));
+        repr.push_str(&format!("17:{:?}", self.hole(17)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("18:{:?}", self.board[18]));
+        repr.push_str(&format!("18:{:?}", self.hole(18)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("19:{:?}", self.board[19]));
+        repr.push_str(&format!("19:{:?}", self.hole(19)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // eigth line
-        repr.push_str(&format!("20:{:?}", self.board[20]));
+        repr.push_str(&format!("20:{:?}", self.hole(20)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("21:{:?}", self.board[21]));
+        repr.push_str(&format!("21:{:?}", self.hole(21)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("22:{:?}", self.board[22]));
+        repr.push_str(&format!("22:{:?}", self.hole(22)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("23:{:?}", self.board[23]));
+        repr.push_str(&format!("23:{:?}", self.hole(23)));
         repr.push('\n');
 
         // ninth line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("24:{:?}", self.board[24]));
+        repr.push_str(&format!("24:{:?}", self.hole(24)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("25:{:?}", self.board[25]));
+        repr.push_str(&format!("25:{:?}", self.hole(25)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("26:{:?}", self.board[26]));
+        repr.push_str(&format!("26:{:?}", self.hole(26)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // tenth line
-        repr.push_str(&format!("27:{:?}", self.board[27]));
+        repr.push_str(&format!("27:{:?}", self.hole(27)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("28:{:?}", self.board[28]));
+        repr.push_str(&format!("28:{:?}", self.hole(28)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("29:{:?}", self.board[29]));
+        repr.push_str(&format!("29:{:?}", self.hole(29)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("30:{:?}", self.board[30]));
+        repr.push_str(&format!("30:{:?}", self.hole(30)));
         repr.push('\n');
 
         // eleventh line
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("31:{:?}", self.board[31]));
+        repr.push_str(&format!("31:{:?}", self.hole(31)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("32:{:?}", self.board[32]));
+        repr.push_str(&format!("32:{:?}", self.hole(32)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("33:{:?}", self.board[33]));
+        repr.push_str(&format!("33:{:?}", self.hole(33)));
         repr.push_str(&space.repeat(1));
         repr.push('\n');
 
         // twelfth line
         repr.push_str(&space.repeat(2));
-        repr.push_str(&format!("34:{:?}", self.board[34]));
+        repr.push_str(&format!("34:{:?}", self.hole(34)));
         repr.push_str(&space.repeat(1));
-        repr.push_str(&format!("35:{:?}", self.board[35]));
+        repr.push_str(&format!("35:{:?}", self.hole(35)));
         repr.push_str(&space.repeat(2));
         repr.push('\n');
 
         // thirteenth line
         repr.push_str(&space.repeat(3));
-        repr.push_str(&format!("36:{:?}", self.board[36]));
+        repr.push_str(&format!("36:{:?}", self.hole(36)));
         repr.push_str(&space.repeat(3));
         repr.push('\n');
 
@@ -282,7 +288,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Move {
     // of the form: (origin, dest)
     Straight(usize, usize),
@@ -290,88 +296,238 @@ pub enum Move {
 }
 use Move::{Diagonal, Straight};
 
-lazy_static! {
-    static ref BLACK_MOVES: [Vec<Move>; 37] = [
-        vec![Diagonal(0, 1), Diagonal(0, 2), Straight(0, 4)],
-        vec![Diagonal(1, 4), Diagonal(1, 3), Straight(1, 7)],
-        vec![Diagonal(2, 4), Diagonal(2, 5), Straight(2, 8)],
-        vec![Diagonal(3, 6), Diagonal(3, 7), Straight(3, 10)],
-        vec![Diagonal(4, 7), Diagonal(4, 8), Straight(4, 11)],
-        vec![Diagonal(5, 8), Diagonal(5, 9), Straight(5, 12)],
-        vec![Diagonal(6, 10), Straight(6, 13)],
-        vec![Diagonal(7, 10), Diagonal(8, 11), Straight(9, 14)],
-        vec![Diagonal(8, 11), Diagonal(8, 12), Straight(8, 15)],
-        vec![Diagonal(9, 12), Straight(9, 16)],
-        vec![Diagonal(10, 13), Diagonal(10, 14), Straight(10, 17)],
-        vec![Diagonal(11, 14), Diagonal(11, 15), Straight(11, 18)],
-        vec![Diagonal(12, 15), Diagonal(12, 16), Straight(12, 19)],
-        vec![Diagonal(13, 17), Straight(13, 20)],
-        vec![Diagonal(14, 17), Diagonal(14, 18), Straight(14, 21)],
-        vec![Diagonal(15, 18), Diagonal(15, 19), Straight(15, 22)],
-        vec![Diagonal(16, 19), Straight(16, 23)],
-        vec![Diagonal(17, 20), Diagonal(17, 21), Straight(17, 24)],
-        vec![Diagonal(18, 21), Diagonal(18, 22), Straight(18, 25)],
-        vec![Diagonal(19, 22), Diagonal(19, 23), Straight(19, 26)],
-        vec![Diagonal(20, 24), Straight(20, 27)],
-        vec![Diagonal(21, 24), Diagonal(21, 25), Straight(21, 28)],
-        vec![Diagonal(22, 25), Diagonal(22, 26), Straight(22, 29)],
-        vec![Diagonal(23, 26), Straight(23, 30)],
-        vec![Diagonal(24, 27), Diagonal(24, 28), Straight(24, 31)],
-        vec![Diagonal(25, 28), Diagonal(25, 29), Straight(25, 32)],
-        vec![Diagonal(26, 29), Diagonal(26, 30), Straight(26, 33)],
-        vec![Diagonal(27, 31)],
-        vec![Diagonal(28, 31), Diagonal(28, 32), Straight(28, 34)],
-        vec![Diagonal(29, 32), Diagonal(29, 33), Straight(29, 35)],
-        vec![Diagonal(30, 33)],
-        vec![Diagonal(31, 34)],
-        vec![Diagonal(32, 34), Diagonal(32, 35), Straight(32, 36)],
-        vec![Diagonal(33, 35)],
-        vec![Diagonal(34, 36)],
-        vec![Diagonal(35, 36)],
-        vec![],
-    ];
+impl Move {
+    pub fn origin(&self) -> usize {
+        match *self {
+            Straight(from, _) | Diagonal(from, _) => from,
+        }
+    }
+
+    pub fn dest(&self) -> usize {
+        match *self {
+            Straight(_, to) | Diagonal(_, to) => to,
+        }
+    }
+
+    // the same move on the mirrored board (see Board::mirror)
+    pub fn mirror(&self) -> Move {
+        self.map_hexes(|hex| MIRRORED_HEXES[hex])
+    }
+
+    // the same move on the color flipped board (see Board::color_flip),
+    // played by the other color
+    pub fn color_flip(&self) -> Move {
+        self.map_hexes(color_flipped_hex)
+    }
+
+    fn map_hexes(&self, f: impl Fn(usize) -> usize) -> Move {
+        match *self {
+            Straight(from, to) => Straight(f(from), f(to)),
+            Diagonal(from, to) => Diagonal(f(from), f(to)),
+        }
+    }
+}
+
+/// the hexes a piece can move to from a given hex, ignoring occupancy
+#[derive(Clone, Copy, Debug)]
+pub struct MoveMasks {
+    pub diagonal: Bitboard,
+    pub straight: Bitboard,
+}
+
+impl MoveMasks {
+    const fn new(diagonal: &[usize], straight: Option<usize>) -> MoveMasks {
+        MoveMasks {
+            diagonal: Bitboard::from_hexes(diagonal),
+            straight: match straight {
+                Some(hex) => Bitboard::from_hex(hex),
+                None => Bitboard::EMPTY,
+            },
+        }
+    }
+
+    pub fn straight_hex(&self) -> Option<usize> {
+        self.straight.first()
+    }
+}
+
+const fn m(diagonal: &[usize], straight: Option<usize>) -> MoveMasks {
+    MoveMasks::new(diagonal, straight)
+}
+
+static BLACK_MOVES: [MoveMasks; NUM_HEXES] = [
+    m(&[1, 2], Some(4)),
+    m(&[3, 4], Some(7)),
+    m(&[4, 5], Some(8)),
+    m(&[6, 7], Some(10)),
+    m(&[7, 8], Some(11)),
+    m(&[8, 9], Some(12)),
+    m(&[10], Some(13)),
+    m(&[10, 11], Some(14)),
+    m(&[11, 12], Some(15)),
+    m(&[12], Some(16)),
+    m(&[13, 14], Some(17)),
+    m(&[14, 15], Some(18)),
+    m(&[15, 16], Some(19)),
+    m(&[17], Some(20)),
+    m(&[17, 18], Some(21)),
+    m(&[18, 19], Some(22)),
+    m(&[19], Some(23)),
+    m(&[20, 21], Some(24)),
+    m(&[21, 22], Some(25)),
+    m(&[22, 23], Some(26)),
+    m(&[24], Some(27)),
+    m(&[24, 25], Some(28)),
+    m(&[25, 26], Some(29)),
+    m(&[26], Some(30)),
+    m(&[27, 28], Some(31)),
+    m(&[28, 29], Some(32)),
+    m(&[29, 30], Some(33)),
+    m(&[31], None),
+    m(&[31, 32], Some(34)),
+    m(&[32, 33], Some(35)),
+    m(&[33], None),
+    m(&[34], None),
+    m(&[34, 35], Some(36)),
+    m(&[35], None),
+    m(&[36], None),
+    m(&[36], None),
+    m(&[], None),
+];
+
+static WHITE_MOVES: [MoveMasks; NUM_HEXES] = [
+    m(&[], None),
+    m(&[0], None),
+    m(&[0], None),
+    m(&[1], None),
+    m(&[1, 2], Some(0)),
+    m(&[2], None),
+    m(&[3], None),
+    m(&[3, 4], Some(1)),
+    m(&[4, 5], Some(2)),
+    m(&[5], None),
+    m(&[6, 7], Some(3)),
+    m(&[7, 8], Some(4)),
+    m(&[8, 9], Some(5)),
+    m(&[10], Some(6)),
+    m(&[10, 11], Some(7)),
+    m(&[11, 12], Some(8)),
+    m(&[12], Some(9)),
+    m(&[13, 14], Some(10)),
+    m(&[14, 15], Some(11)),
+    m(&[15, 16], Some(12)),
+    m(&[17], Some(13)),
+    m(&[17, 18], Some(14)),
+    m(&[18, 19], Some(15)),
+    m(&[19], Some(16)),
+    m(&[20, 21], Some(17)),
+    m(&[21, 22], Some(18)),
+    m(&[22, 23], Some(19)),
+    m(&[24], Some(20)),
+    m(&[24, 25], Some(21)),
+    m(&[25, 26], Some(22)),
+    m(&[26], Some(23)),
+    m(&[27, 28], Some(24)),
+    m(&[28, 29], Some(25)),
+    m(&[29, 30], Some(26)),
+    m(&[31, 32], Some(28)),
+    m(&[32, 33], Some(29)),
+    m(&[34, 35], Some(32)),
+];
+
+// how many hexes are in each row, from black's side of the board
+const ROW_LENGTHS: [usize; 13] = [1, 2, 3, 4, 3, 4, 3, 4, 3, 4, 3, 2, 1];
+
+const fn mirrored_hexes() -> [usize; NUM_HEXES] {
+    let mut mirrored = [0; NUM_HEXES];
+    let (mut row, mut row_start) = (0, 0);
+    while row < ROW_LENGTHS.len() {
+        let mut i = 0;
+        while i < ROW_LENGTHS[row] {
+            mirrored[row_start + i] = row_start + ROW_LENGTHS[row] - 1 - i;
+            i += 1;
+        }
+        row_start += ROW_LENGTHS[row];
+        row += 1;
+    }
+    mirrored
+}
+
+/// the hex on the other side of the board's vertical axis, in the same row
+pub const MIRRORED_HEXES: [usize; NUM_HEXES] = mirrored_hexes();
+
+/// the hex a half turn away, around the middle of the board. black's moves
+/// from a hex are white's moves from this one, turned around
+pub const fn color_flipped_hex(hex: usize) -> usize {
+    NUM_HEXES - 1 - hex
 }
 
-lazy_static! {
-    static ref WHITE_MOVES: [Vec<Move>; 37] = [
-        vec![],
-        vec![Diagonal(1, 0)],
-        vec![Diagonal(2, 0)],
-        vec![Diagonal(3, 1)],
-        vec![Diagonal(4, 2), Diagonal(4, 1), Straight(4, 0)],
-        vec![Diagonal(5, 2)],
-        vec![Diagonal(6, 3)],
-        vec![Diagonal(7, 4), Diagonal(7, 3), Straight(7, 1)],
-        vec![Diagonal(8, 5), Diagonal(8, 4), Straight(8, 2)],
-        vec![Diagonal(9, 5)],
-        vec![Diagonal(10, 7), Diagonal(10, 6), Straight(10, 3)],
-        vec![Diagonal(11, 8), Diagonal(11, 7), Straight(11, 4)],
-        vec![Diagonal(12, 9), Diagonal(12, 8), Straight(12, 5)],
-        vec![Diagonal(13, 10), Straight(13, 6)],
-        vec![Diagonal(14, 11), Diagonal(14, 10), Straight(14, 7)],
-        vec![Diagonal(15, 12), Diagonal(15, 11), Straight(15, 8)],
-        vec![Diagonal(16, 12), Straight(16, 9)],
-        vec![Diagonal(17, 14), Diagonal(17, 13), Straight(17, 10)],
-        vec![Diagonal(18, 15), Diagonal(18, 14), Straight(18, 11)],
-        vec![Diagonal(19, 16), Diagonal(19, 15), Straight(19, 12)],
-        vec![Diagonal(20, 17), Straight(20, 13)],
-        vec![Diagonal(21, 18), Diagonal(21, 17), Straight(21, 14)],
-        vec![Diagonal(22, 19), Diagonal(22, 18), Straight(22, 15)],
-        vec![Diagonal(23, 19), Straight(23, 16)],
-        vec![Diagonal(24, 21), Diagonal(24, 20), Straight(24, 17)],
-        vec![Diagonal(25, 22), Diagonal(25, 21), Straight(25, 18)],
-        vec![Diagonal(26, 23), Diagonal(26, 22), Straight(26, 19)],
-        vec![Diagonal(27, 24), Straight(27, 20)],
-        vec![Diagonal(28, 25), Diagonal(28, 24), Straight(28, 21)],
-        vec![Diagonal(29, 26), Diagonal(29, 25), Straight(29, 22)],
-        vec![Diagonal(30, 26), Straight(30, 23)],
-        vec![Diagonal(31, 28), Diagonal(31, 27), Straight(31, 24)],
-        vec![Diagonal(32, 29), Diagonal(32, 28), Straight(32, 25)],
-        vec![Diagonal(33, 30), Diagonal(33, 29), Straight(33, 26)],
-        vec![Diagonal(34, 32), Diagonal(34, 31), Straight(34, 28)],
-        vec![Diagonal(35, 33), Diagonal(35, 32), Straight(35, 29)],
-        vec![Diagonal(36, 35), Diagonal(36, 34), Straight(36, 32)],
-    ];
+/// every piece has at most three moves, so this bounds any position
+pub const MAX_MOVES: usize = 3 * NUM_HEXES;
+
+/// a fixed-capacity list of moves, so move generation never allocates
+#[derive(Clone, Copy)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+impl MoveList {
+    pub fn new() -> MoveList {
+        MoveList {
+            moves: [Straight(0, 0); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, m: Move) {
+        self.moves[self.len] = m;
+        self.len += 1;
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = std::iter::Take<std::array::IntoIter<Move, MAX_MOVES>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.into_iter().take(self.len)
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
 }
 
 impl Default for Board {
@@ -382,16 +538,71 @@ fn default() -> Self {
 
 impl Board {
     pub fn new() -> Board {
-        let mut pieces = [Hole(None); 37];
-        #[allow(clippy::needless_range_loop)]
-        for i in 0..=36 {
-            pieces[i] = match i {
-                i if i < 10 => Hole(Some(Piece::Black)),
-                i if i > 26 => Hole(Some(Piece::White)),
-                _ => Hole(None),
+        Board::from_pieces(
+            Bitboard::from_hexes(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
+            Bitboard::from_hexes(&[27, 28, 29, 30, 31, 32, 33, 34, 35, 36]),
+        )
+    }
+
+    pub fn from_pieces(black: Bitboard, white: Bitboard) -> Board {
+        let mut board = Board {
+            pieces: [black, white],
+            hash: 0,
+        };
+        board.hash = board.compute_hash();
+        board
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    // computes the zobrist hash from scratch, rather than incrementally
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for (color, pieces) in self.pieces.iter().enumerate() {
+            for hex in pieces.iter() {
+                hash ^= PIECE_KEYS[color][hex];
+            }
+        }
+        hash
+    }
+
+    fn insert_piece(&mut self, color: usize, hex: usize) {
+        self.pieces[color].insert(hex);
+        self.hash ^= PIECE_KEYS[color][hex];
+    }
+
+    fn remove_piece(&mut self, color: usize, hex: usize) {
+        self.pieces[color].remove(hex);
+        self.hash ^= PIECE_KEYS[color][hex];
+    }
+
+    pub fn hole(&self, idx: usize) -> Hole {
+        if self.pieces[0].contains(idx) {
+            Hole(Some(Piece::Black))
+        } else if self.pieces[1].contains(idx) {
+            Hole(Some(Piece::White))
+        } else {
+            Hole(None)
+        }
+    }
+
+    pub fn set_hole(&mut self, idx: usize, hole: Hole) {
+        for color in 0..2 {
+            if self.pieces[color].contains(idx) {
+                self.remove_piece(color, idx);
             }
         }
-        Board { board: pieces }
+        match hole {
+            Hole(Some(Piece::Black)) => self.insert_piece(0, idx),
+            Hole(Some(Piece::White)) => self.insert_piece(1, idx),
+            Hole(None) => (),
+        }
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.pieces[0] | self.pieces[1]
     }
 
     pub fn possible_move(
@@ -400,97 +611,147 @@ pub fn possible_move(
         to: &usize,
         turn: u8,
     ) -> Option<Move> {
-        let lookup = match turn {
-            // need to use * to get the true type (not that created by lazy_static)
-            // and then we use a reference to it
-            0 => &*BLACK_MOVES,
-            1 => &*WHITE_MOVES,
-            _ => panic!("it's impossible for more than two players to move"),
-        };
+        let masks = get_moves_of_piece(turn, from);
+
+        if masks.diagonal.contains(*to) {
+            Some(Diagonal(*from, *to))
+        } else if masks.straight.contains(*to) {
+            Some(Straight(*from, *to))
+        } else {
+            None
+        }
+    }
+
+    pub fn current_players_pieces(&self, turn: u8) -> Bitboard {
+        self.pieces[turn_index(turn)]
+    }
 
-        match lookup.get(from.to_owned()) {
-            Some(possible_moves) => possible_moves
-                .iter()
-                .find(|elt| match elt {
-                    Straight(_, m) | Diagonal(_, m) => m == to,
-                })
-                .copied(),
-            None => None,
+    // diagonal moves need an empty destination, while straight moves may
+    // also capture an opponent's piece head-on
+    fn legal_destinations(&self, turn: u8, origin: usize) -> MoveMasks {
+        let masks = get_moves_of_piece(turn, &origin);
+        MoveMasks {
+            diagonal: masks.diagonal & !self.occupied(),
+            straight: masks.straight & !self.pieces[turn_index(turn)],
         }
     }
 
-    pub fn current_players_pieces(&self, turn: u8) -> Vec<usize> {
-        let piece_type = match turn {
-            0 => Piece::Black,
-            1 => Piece::White,
-            _ => panic!("it's impossible for more than two players to move"),
-        };
+    pub fn all_valid_moves(&self, turn: u8) -> MoveList {
+        let mut moves = MoveList::new();
 
-        // current_pieces is a vector of all pieces for the given player
-        self.board
-            .into_iter()
-            .enumerate()
-            .filter_map(|(idx, elt)| match elt {
-                Hole(Some(p)) => {
-                    if p == piece_type {
-                        Some(idx)
-                    } else {
-                        None
-                    }
-                }
-                Hole(None) => None,
-            })
-            .collect::<Vec<usize>>()
+        for origin in self.current_players_pieces(turn) {
+            let dests = self.legal_destinations(turn, origin);
+            for dest in dests.diagonal {
+                moves.push(Diagonal(origin, dest));
+            }
+            for dest in dests.straight {
+                moves.push(Straight(origin, dest));
+            }
+        }
+
+        moves
     }
 
-    pub fn all_valid_moves(&self, turn: u8) -> Vec<Move> {
-        let lookup = match turn {
-            // need to use * to get the true type (not that created by lazy_static)
-            // and then we use a reference to it
-            0 => &*BLACK_MOVES,
-            1 => &*WHITE_MOVES,
-            _ => panic!("it's impossible for more than two players to move"),
-        };
+    pub fn has_valid_move(&self, turn: u8) -> bool {
+        self.current_players_pieces(turn).into_iter().any(|origin| {
+            let dests = self.legal_destinations(turn, origin);
+            !(dests.diagonal | dests.straight).is_empty()
+        })
+    }
 
-        // current_pieces is a vector of all pieces for the given player
-        let current_pieces = &self.current_players_pieces(turn);
+    // this function assumes that the move is legal. returns whether an
+    // opponent's piece was captured
+    pub fn apply_move(&mut self, from: usize, to: usize, turn: u8) -> bool {
+        let (own, other) = (turn_index(turn), 1 - turn_index(turn));
+        let captured = self.pieces[other].contains(to);
 
-        current_pieces
-            .iter()
-            .flat_map(|elt| {
-                let move_dests = lookup.get(elt.to_owned());
-                match move_dests {
-                    Some(moves) => moves.to_vec(),
-                    None => vec![],
-                }
+        self.remove_piece(own, from);
+        self.insert_piece(own, to);
+        if captured {
+            self.remove_piece(other, to);
+        }
+
+        captured
+    }
+
+    // the hexes directly ahead of each of this player's pieces
+    pub fn straight_hexes(&self, turn: u8) -> Bitboard {
+        self.current_players_pieces(turn)
+            .into_iter()
+            .fold(Bitboard::EMPTY, |acc, origin| {
+                acc | get_moves_of_piece(turn, &origin).straight
             })
-            .collect::<Vec<Move>>()
     }
 
-    pub fn get_straight_hex(&self, turn: u8, index: usize) -> Option<&usize> {
-        let piece_moves = get_moves_of_piece(turn, &index);
+    // reverts apply_move(), restoring the opponent's piece if one was captured
+    pub fn unapply_move(
+        &mut self,
+        from: usize,
+        to: usize,
+        turn: u8,
+        captured: bool,
+    ) {
+        let (own, other) = (turn_index(turn), 1 - turn_index(turn));
+
+        self.remove_piece(own, to);
+        self.insert_piece(own, from);
+        if captured {
+            self.insert_piece(other, to);
+        }
+    }
+
+    // the board reflected left to right. the rules are symmetric, so it's
+    // worth exactly as much as the original
+    pub fn mirror(&self) -> Board {
+        let reflect = |pieces: Bitboard| {
+            pieces.iter().fold(Bitboard::EMPTY, |acc, hex| {
+                acc | Bitboard::from_hex(MIRRORED_HEXES[hex])
+            })
+        };
+        Board::from_pieces(reflect(self.pieces[0]), reflect(self.pieces[1]))
+    }
 
-        // in the move hashmaps up above, the straight move is always last
-        let potential_straight_move = piece_moves.last();
+    // the board turned a half turn, with the colors swapped. the position is
+    // the same for the other color, so it's worth the negation of the
+    // original (with the other player to move)
+    pub fn color_flip(&self) -> Board {
+        let rotate = |pieces: Bitboard| {
+            pieces.iter().fold(Bitboard::EMPTY, |acc, hex| {
+                acc | Bitboard::from_hex(color_flipped_hex(hex))
+            })
+        };
+        Board::from_pieces(rotate(self.pieces[1]), rotate(self.pieces[0]))
+    }
 
-        match potential_straight_move {
-            Some(Straight(_, dest)) => Some(dest),
-            _ => None,
+    // the smaller of the board and its mirror image, so both map to one
+    // position (for deduplicating positions). the color flip is left out,
+    // since it changes whose turn it is and negates the value
+    pub fn canonical(&self) -> Board {
+        let mirrored = self.mirror();
+        let key = |board: &Board| (board.pieces[0].0, board.pieces[1].0);
+        match key(&mirrored) < key(self) {
+            true => mirrored,
+            false => *self,
         }
     }
 
+    pub fn get_straight_hex(&self, turn: u8, index: usize) -> Option<usize> {
+        get_moves_of_piece(turn, &index).straight_hex()
+    }
+
     #[allow(clippy::result_unit_err)]
     pub fn from_repr_string(s: &str) -> Result<Board, ()> {
-        let mut pieces: [Hole; 37] = [Hole(None); 37];
+        let mut board = Board::from_pieces(Bitboard::EMPTY, Bitboard::EMPTY);
         let mut idx = 0;
         for char in s.chars() {
             match char {
+                'b' | 'w' if idx >= NUM_HEXES => return Err(()),
                 'b' => {
-                    pieces[idx] = Hole(Some(Piece::Black));
+                    board.set_hole(idx, Hole(Some(Piece::Black)));
                     idx += 1;
                 }
                 'w' => {
-                    pieces[idx] = Hole(Some(Piece::White));
+                    board.set_hole(idx, Hole(Some(Piece::White)));
                     idx += 1;
                 }
                 '/' => (),
@@ -500,13 +761,16 @@ pub fn from_repr_string(s: &str) -> Result<Board, ()> {
                 },
             }
         }
-        Ok(Board { board: pieces })
+        Ok(board)
     }
 
     pub fn to_repr_string(&self) -> String {
+        const LINE_ENDS: [usize; 12] =
+            [0, 2, 5, 9, 12, 16, 19, 23, 26, 30, 33, 35];
+
         let mut result = "".to_string();
-        for (idx, &h) in self.board.iter().enumerate() {
-            match h {
+        for idx in 0..NUM_HEXES {
+            match self.hole(idx) {
                 Hole(Some(Piece::Black)) => result.push('b'),
                 Hole(Some(Piece::White)) => result.push('w'),
                 Hole(None) => match result.chars().last() {
@@ -520,7 +784,7 @@ pub fn to_repr_string(&self) -> String {
                 },
             };
 
-            if vec![0, 2, 5, 9, 12, 16, 19, 23, 26, 30, 33, 35].contains(&idx) {
+            if LINE_ENDS.contains(&idx) {
                 result.push('/');
             }
         }
@@ -528,10 +792,17 @@ pub fn to_repr_string(&self) -> String {
     }
 }
 
-pub fn get_moves_of_piece(turn: u8, piece: &usize) -> &'static Vec<Move> {
+fn turn_index(turn: u8) -> usize {
+    match turn {
+        0 | 1 => turn as usize,
+        _ => panic!("it's impossible for more than two players to move"),
+    }
+}
+
+pub fn get_moves_of_piece(turn: u8, piece: &usize) -> &'static MoveMasks {
     let lookup = match turn {
-        0 => &*BLACK_MOVES,
-        1 => &*WHITE_MOVES,
+        0 => &BLACK_MOVES,
+        1 => &WHITE_MOVES,
         _ => panic!("Invalid Turn (must be zero or one"),
     };
 
@@ -1,15 +1,15 @@
 use super::{
-    board::Board,
-    board::Hole,
-    board::Move,
-    board::Move::Diagonal,
-    board::{get_moves_of_piece, Move::Straight},
+    board::{
+        Board, Hole, Move,
+        Move::{Diagonal, Straight},
+        MoveList,
+    },
     pieces::Piece,
-    player::Player,
-    player::PossiblePlayer,
-    player::AI,
+    player::{Player, PossiblePlayer, AI},
+    zobrist::SIDE_KEY,
 };
 use crate::DATABASE_URL;
+use rand::RngCore;
 use rusqlite::{params, Connection, Result};
 use std::fmt::{self};
 
@@ -45,10 +45,29 @@ pub fn build(self) -> State {
             board: self.board.to_owned(),
             players: self.players,
             state_history: vec![],
+            last_move: None,
+            plies: 0,
         }
     }
 }
 
+/// everything needed to take back a move played with `State::make_move`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Undo {
+    pub mv: Move,
+    pub captured: bool,
+    active: bool,
+    winner: Option<u8>,
+    current_turn: u8,
+}
+
+impl Undo {
+    // the player who made this move
+    pub fn player(&self) -> u8 {
+        self.current_turn
+    }
+}
+
 #[derive(Clone)]
 pub struct State {
     pub active: bool,
@@ -57,6 +76,12 @@ pub struct State {
     pub board: Board,
     pub players: [PossiblePlayer; 2],
     pub state_history: Vec<String>,
+    // the move move_piece() last played, for an interactive game to keep
+    // its own undo stack with
+    pub last_move: Option<Undo>,
+    // the moves played since the start of the game (or since the position
+    // it was read from)
+    pub plies: usize,
 }
 
 impl fmt::Debug for State {
@@ -117,13 +142,13 @@ pub fn can_move(
         let current_player_pieces = self.get_pieces_type_from_idx(turn);
 
         let valid_start =
-            self.board.board[*from] == Hole(Some(current_player_pieces));
+            self.board.hole(*from) == Hole(Some(current_player_pieces));
 
         if valid_start {
             match self.board.possible_move(from, to, turn) {
                 Some(m @ Move::Diagonal(_a, d))
                 | Some(m @ Move::Straight(_a, d)) => {
-                    match self.board.board[d].0 {
+                    match self.board.hole(d).0 {
                     Some(existing_piece) if existing_piece == current_player_pieces => {
                         Err("can't occupy the same space as another one of your pieces")
                     }
@@ -149,19 +174,16 @@ pub fn move_piece(
         to: usize,
         capture: bool,
     ) -> Result<(), &'static str> {
-        let current_player_pieces =
-            self.get_pieces_type_from_idx(self.current_turn);
-
         match (self.can_move(&from, &to, self.current_turn), capture) {
             (Ok(true), true) => {
-                self.move_piece_aux(from, to, current_player_pieces);
+                self.move_piece_aux(from, to);
                 Ok(())
             }
             (Ok(true), false) => {
                 Err("you don't have permission to capture this piece")
             }
             (Ok(false), _) => {
-                self.move_piece_aux(from, to, current_player_pieces);
+                self.move_piece_aux(from, to);
                 Ok(())
             }
             (Err(e), _) => Err(e),
@@ -169,15 +191,30 @@ pub fn move_piece(
     }
 
     // this function assumes that moving the piece is legal / valid
-    fn move_piece_aux(
-        &mut self,
-        from: usize,
-        to: usize,
-        current_player_pieces: Piece,
-    ) {
+    fn move_piece_aux(&mut self, from: usize, to: usize) {
+        let m = self
+            .board
+            .possible_move(&from, &to, self.current_turn)
+            .expect("a validated move must be in the move tables");
+        self.last_move = Some(self.make_move(m));
+    }
+
+    // make_move() plays a move without validating it (it must come from
+    // current_possible_moves) or recording it in last_move. the
+    // returned record restores the position with unmake_move()
+    pub fn make_move(&mut self, m: Move) -> Undo {
+        let (Diagonal(from, to) | Straight(from, to)) = m;
+        let mut undo = Undo {
+            mv: m,
+            captured: false,
+            active: self.active,
+            winner: self.winner,
+            current_turn: self.current_turn,
+        };
+
         // return state with piece moved
-        self.board.board[from] = Hole(None);
-        self.board.board[to] = Hole(Some(current_player_pieces));
+        undo.captured = self.board.apply_move(from, to, self.current_turn);
+        self.plies += 1;
 
         // if white just moved, and black now can't move, white wins
         // if black just moved, and white now can't move, black wins
@@ -199,19 +236,72 @@ fn move_piece_aux(
             // one move per turn
             self.current_turn = 1 - self.current_turn;
         }
+
+        undo
+    }
+
+    pub fn unmake_move(&mut self, undo: Undo) {
+        let (Diagonal(from, to) | Straight(from, to)) = undo.mv;
+        self.board
+            .unapply_move(from, to, undo.current_turn, undo.captured);
+
+        self.active = undo.active;
+        self.winner = undo.winner;
+        self.current_turn = undo.current_turn;
+        self.plies -= 1;
+    }
+
+    // take_back() reverts the last move played (see last_move), along with
+    // its position in the state history if it was added
+    pub fn take_back(&mut self, undo: Undo) {
+        if self.state_history.last() == Some(&self.to_repr_string()) {
+            self.state_history.pop();
+        }
+        self.unmake_move(undo);
+        self.last_move = None;
+    }
+
+    // zobrist hash of the piece placement and the side to move
+    pub fn hash(&self) -> u64 {
+        match self.current_turn {
+            0 => self.board.hash(),
+            _ => self.board.hash() ^ SIDE_KEY,
+        }
+    }
+
+    // the position reflected left to right (see Board::mirror). the
+    // histories don't carry over
+    pub fn mirror(&self) -> State {
+        State {
+            board: self.board.mirror(),
+            state_history: vec![],
+            last_move: None,
+            ..self.clone()
+        }
     }
 
-    pub fn current_possible_moves(&self, turn: u8) -> Vec<Move> {
+    // the position with the colors swapped (see Board::color_flip), so the
+    // other color is to move, or has won, and the players trade places. the
+    // histories don't carry over
+    pub fn color_flip(&self) -> State {
+        let [black, white] = self.players.clone();
+        State {
+            active: self.active,
+            winner: self.winner.map(|winner| 1 - winner),
+            current_turn: 1 - self.current_turn,
+            board: self.board.color_flip(),
+            players: [white, black],
+            state_history: vec![],
+            last_move: None,
+            plies: self.plies,
+        }
+    }
+
+    pub fn current_possible_moves(&self, turn: u8) -> MoveList {
         if !self.active {
-            return vec![];
+            return MoveList::new();
         }
-        Board::all_valid_moves(&self.board, turn)
-            .into_iter()
-            .filter(|&m| {
-                let (Diagonal(origin, dest) | Straight(origin, dest)) = m;
-                matches!(self.can_move(&origin, &dest, turn), Ok(_))
-            })
-            .collect::<Vec<Move>>()
+        self.board.all_valid_moves(turn)
     }
 
     pub fn get_pieces_type_from_player(&self, p: &PossiblePlayer) -> Piece {
@@ -239,17 +329,17 @@ pub fn play_one_turn(&mut self) {
         current_player.one_turn(self);
     }
 
-    fn has_a_possible_move(&mut self, turn: u8) -> bool {
-        for origin in self.board.current_players_pieces(turn).iter() {
-            for mv in get_moves_of_piece(turn, origin).iter() {
-                let (Straight(origin, dest) | Diagonal(origin, dest)) = mv;
-                if self.can_move(origin, dest, turn).is_ok() {
-                    return true;
-                }
-            }
-        }
+    // play_one_turn_with_rng() is play_one_turn(), with the player's random
+    // choices drawn from `rng` (so the game can be replayed)
+    pub fn play_one_turn_with_rng(&mut self, rng: &mut dyn RngCore) {
+        let current_player =
+            self.players[self.current_turn as usize].to_owned();
 
-        false
+        current_player.one_turn_with_rng(self, rng);
+    }
+
+    fn has_a_possible_move(&self, turn: u8) -> bool {
+        self.board.has_valid_move(turn)
     }
 
     pub fn add_to_state_history(&mut self) {
@@ -274,6 +364,8 @@ pub fn from_repr_string(s: &str) -> Result<State, ()> {
                     ],
                     board: b?,
                     state_history: vec![s.to_string()],
+                    last_move: None,
+                    plies: 0,
                 })
             }
             Some("w:") => {
@@ -291,6 +383,8 @@ pub fn from_repr_string(s: &str) -> Result<State, ()> {
                     ],
                     board: b?,
                     state_history: vec![s.to_string()],
+                    last_move: None,
+                    plies: 0,
                 })
             }
             _ => Err(()),
@@ -328,6 +422,44 @@ pub fn push_game(&self, conn: &mut Connection) -> Result<i64> {
         Ok(last_id)
     }
 
+    // push_game_to_dataset() records the game and its states under a
+    // dataset tag, so training sets can be picked out later
+    pub fn push_game_to_dataset(
+        &self,
+        conn: &mut Connection,
+        dataset: &str,
+    ) -> Result<i64> {
+        conn.execute(
+            r#"
+            INSERT INTO game_table (result, dataset)
+            VALUES (?1, ?2)
+            "#,
+            params![self.winner.unwrap(), dataset],
+        )?;
+        let game_id = conn.last_insert_rowid();
+        self.push_game_state_history(conn, game_id)?;
+        Ok(game_id)
+    }
+
+    // push_game_to_experiment() records a game of the genetic process with
+    // the experiment it was played for
+    pub fn push_game_to_experiment(
+        &self,
+        conn: &mut Connection,
+        experiment_id: i64,
+    ) -> Result<i64> {
+        conn.execute(
+            r#"
+            INSERT INTO game_table (result, experiment_id)
+            VALUES (?1, ?2)
+            "#,
+            params![self.winner.unwrap(), experiment_id],
+        )?;
+        let game_id = conn.last_insert_rowid();
+        self.push_game_state_history(conn, game_id)?;
+        Ok(game_id)
+    }
+
     pub fn push_game_state_history(
         &self,
         conn: &mut Connection,
@@ -1,4 +1,7 @@
+pub mod bitboard;
 pub mod board;
 pub mod gamestate;
+pub mod perft;
 pub mod pieces;
 pub mod player;
+pub mod zobrist;
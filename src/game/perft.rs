@@ -0,0 +1,72 @@
+use std::ops::AddAssign;
+
+use super::board::Move;
+use super::gamestate::State;
+
+/// how many move sequences of some length there are from a position, and
+/// how many of them end with a capture, or a win
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftCounts {
+    pub nodes: u64,
+    pub captures: u64,
+    pub wins: u64,
+}
+
+impl AddAssign for PerftCounts {
+    fn add_assign(&mut self, other: PerftCounts) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.wins += other.wins;
+    }
+}
+
+// perft() counts the positions exactly `depth` plies from `state`. games
+// that end sooner have no positions after them, so they aren't counted
+pub fn perft(state: &mut State, depth: usize) -> PerftCounts {
+    if depth == 0 {
+        return PerftCounts {
+            nodes: 1,
+            ..PerftCounts::default()
+        };
+    }
+
+    let mut counts = PerftCounts::default();
+    for m in state.current_possible_moves(state.current_turn) {
+        counts += perft_move(state, m, depth);
+    }
+    counts
+}
+
+// divide() is perft(), broken down by the first move
+pub fn divide(state: &mut State, depth: usize) -> Vec<(Move, PerftCounts)> {
+    if depth == 0 {
+        return vec![];
+    }
+    state
+        .current_possible_moves(state.current_turn)
+        .into_iter()
+        .map(|m| (m, perft_move(state, m, depth)))
+        .collect()
+}
+
+fn perft_move(state: &mut State, m: Move, depth: usize) -> PerftCounts {
+    debug_assert!(
+        state
+            .can_move(&m.origin(), &m.dest(), state.current_turn)
+            .is_ok(),
+        "{m:?} was generated for {}, but isn't legal",
+        state.to_repr_string()
+    );
+
+    let undo = state.make_move(m);
+    let counts = match depth {
+        1 => PerftCounts {
+            nodes: 1,
+            captures: undo.captured as u64,
+            wins: state.winner.is_some() as u64,
+        },
+        _ => perft(state, depth - 1),
+    };
+    state.unmake_move(undo);
+    counts
+}
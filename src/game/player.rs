@@ -1,23 +1,27 @@
+use crate::ai::book::{BookSelection, OPENING_BOOK};
 use crate::ai::heuristics::{
     normalize_weights, HeuristicWeights, Weights, NUM_HEURISTICS,
 };
+use crate::ai::mcts::{Mcts, Rollout, DEFAULT_EXPLORATION};
+use crate::ai::nn::{NnModel, Snapshot};
+use crate::ai::search::{SearchConfig, Searcher};
 use crate::ai::tree::SearchLimit;
 use crate::game::board::Move;
 
 use crate::ai::tree::get_best_move;
 
-use super::gamestate::State;
+use super::gamestate::{State, Undo};
 use core::fmt::Debug;
 
 use log::trace;
-use pyo3::types::{PyString, PyTuple};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::{fmt, io};
 
 use crate::game::player::Move::{Diagonal, Straight};
 
 use ordered_float::OrderedFloat;
-use pyo3::prelude::*;
 
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct Person {
@@ -35,19 +39,29 @@ fn name(&self) -> String {
         self.name.clone()
     }
 
-    fn one_turn(&self, state: &mut State) {
-        println!("Input your move:");
+    fn one_turn_with_rng(&self, state: &mut State, _rng: &mut dyn RngCore) {
+        // nothing to take back outside of an interactive game
+        self.play_turn(state, &mut vec![]);
+    }
+}
+
+impl Person {
+    // play_turn() plays the move the person inputs, or takes back their last
+    // one (and the opponent's since) from `undos`, the moves of the game so
+    // far
+    pub fn play_turn(&self, state: &mut State, undos: &mut Vec<Undo>) {
+        println!("Input your move (or \"undo\" to take back your last move):");
 
         let mut input = String::new();
         match io::stdin().read_line(&mut input) {
             Ok(_) => {
-                match handle_move_input(state, input.trim()) {
+                match handle_move_input(state, input.trim(), undos) {
                     Ok(_) => (),
                     Err(e) => {
                         println!(
                             "Couldn't process that move ({e}). Please try again"
                         );
-                        self.one_turn(state);
+                        self.play_turn(state, undos);
                     }
                 };
             }
@@ -59,8 +73,10 @@ fn one_turn(&self, state: &mut State) {
 fn handle_move_input(
     game: &mut State,
     input: &str,
+    undos: &mut Vec<Undo>,
 ) -> Result<(), &'static str> {
     match input.split('-').collect::<Vec<&str>>()[..] {
+        ["undo"] => take_back_turn(game, undos),
         [a, b] => {
             let from = a.parse::<usize>();
             let to = b.parse::<usize>();
@@ -73,11 +89,38 @@ fn handle_move_input(
     }
 }
 
+// take_back_turn() reverts moves until the current player's last move has
+// been taken back, so it's their turn again
+fn take_back_turn(
+    game: &mut State,
+    undos: &mut Vec<Undo>,
+) -> Result<(), &'static str> {
+    let player = game.current_turn;
+    if !undos.iter().any(|undo| undo.player() == player) {
+        return Err("you haven't made a move to take back");
+    }
+
+    while let Some(undo) = undos.pop() {
+        game.take_back(undo);
+        println!("Took back {:?}", undo.mv);
+        if undo.player() == player {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct AI {
     name: String,
     pub weights: Weights,
     limit: SearchLimit,
+    // not stored with the agent, so older recovery tables still load
+    #[serde(skip)]
+    search: SearchConfig,
+    #[serde(skip)]
+    book: BookSelection,
 }
 
 impl Default for AI {
@@ -86,6 +129,8 @@ fn default() -> Self {
             name: String::default(),
             weights: [1.0; NUM_HEURISTICS],
             limit: SearchLimit::default(),
+            search: SearchConfig::default(),
+            book: BookSelection::default(),
         }
     }
 }
@@ -104,6 +149,8 @@ pub fn from_name(name: String) -> AI {
             name,
             weights: [1.0; NUM_HEURISTICS],
             limit: SearchLimit::default(),
+            search: SearchConfig::default(),
+            book: BookSelection::default(),
         }
     }
 
@@ -116,6 +163,8 @@ pub fn from_weights(name: String, vec_weights: Vec<f64>) -> AI {
             name,
             weights: array_weights,
             limit: SearchLimit::default(),
+            search: SearchConfig::default(),
+            book: BookSelection::default(),
         }
     }
 
@@ -126,8 +175,20 @@ pub fn new(name: String, weights: Weights, limit: SearchLimit) -> AI {
             name,
             weights,
             limit,
+            search: SearchConfig::default(),
+            book: BookSelection::default(),
         }
     }
+
+    pub fn with_search_config(mut self, search: SearchConfig) -> AI {
+        self.search = search;
+        self
+    }
+
+    pub fn with_book_selection(mut self, book: BookSelection) -> AI {
+        self.book = book;
+        self
+    }
 }
 
 impl Player for AI {
@@ -135,8 +196,28 @@ fn name(&self) -> String {
         self.name.clone()
     }
 
-    fn one_turn(&self, state: &mut State) {
-        let sugg_move = get_best_move(state, &self.limit, &self.weights);
+    fn one_turn_with_rng(&self, state: &mut State, mut rng: &mut dyn RngCore) {
+        // openings the book knows are played without searching
+        if let Some(book_move) = OPENING_BOOK
+            .as_ref()
+            .and_then(|book| book.probe(state, self.book, &mut rng))
+        {
+            let (Move::Diagonal(origin, dest) | Move::Straight(origin, dest)) =
+                book_move.mv;
+            state
+                .move_piece(origin, dest, true)
+                .expect("could not play the book move");
+            trace!(
+                "Played {:?} from the opening book ({} games, {:.1}% won)",
+                book_move.mv,
+                book_move.games,
+                100.0 * book_move.win_rate
+            );
+            return;
+        }
+
+        let sugg_move =
+            get_best_move(state, &self.limit, &self.weights, &self.search);
 
         let (Move::Diagonal(origin, dest) | Move::Straight(origin, dest)) =
             sugg_move.suggestion;
@@ -149,39 +230,70 @@ fn one_turn(&self, state: &mut State) {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MCTS {
+    name: String,
+    pub weights: Weights,
+    limit: SearchLimit,
+    rollout: Rollout,
+}
+
+impl MCTS {
+    pub fn new(
+        name: String,
+        weights: Weights,
+        limit: SearchLimit,
+        rollout: Rollout,
+    ) -> MCTS {
+        assert!(
+            !matches!(limit, SearchLimit::Depth(_)),
+            "MCTS takes a time or iterations limit, not {limit}"
+        );
+        MCTS {
+            name,
+            weights,
+            limit,
+            rollout,
+        }
+    }
+}
+
+impl Player for MCTS {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn one_turn_with_rng(&self, state: &mut State, mut rng: &mut dyn RngCore) {
+        let sugg_move = Mcts::new(
+            &self.weights,
+            self.rollout,
+            DEFAULT_EXPLORATION,
+        )
+        .search(state, &self.limit, &mut rng);
+
+        let (Move::Diagonal(origin, dest) | Move::Straight(origin, dest)) =
+            sugg_move.suggestion;
+
+        state
+            .move_piece(origin, dest, true)
+            .expect("could not play the MCTS-suggested move");
+
+        trace!("{sugg_move:#?}");
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct NN {
     name: String,
-    path: String,
+    model: NnModel,
 }
 
 impl NN {
     pub fn new(name: String, path: String) -> NN {
-        NN { name, path }
-    }
-
-    fn run_python_nn(&self, state_string_repr: &str) -> PyResult<f64> {
-        let code_location = "neuralnet/predict.py";
-        let code = std::fs::read_to_string(code_location)?;
-
-        pyo3::prepare_freethreaded_python();
-        let black_score = Python::with_gil(|py| -> PyResult<f64> {
-            let module =
-                PyModule::from_code(py, &code, "predict.py", "predict.py")?;
-
-            let nn_function = module.getattr("run_nn")?;
-            let args = PyTuple::new(
-                py,
-                [
-                    PyString::new(py, state_string_repr),
-                    PyString::new(py, &self.path),
-                ],
-            );
-            let result = nn_function.call(args, None)?.extract::<f64>()?;
-            Ok(result)
-        });
-
-        Ok(black_score.unwrap())
+        NN {
+            name,
+            model: NnModel::load(&path).expect("could not load the NN model"),
+        }
     }
 }
 
@@ -190,30 +302,19 @@ fn name(&self) -> String {
         self.name.clone()
     }
 
-    fn one_turn(&self, state: &mut State) {
+    fn one_turn_with_rng(&self, state: &mut State, _rng: &mut dyn RngCore) {
         let next_move_vec = state.current_possible_moves(state.current_turn);
-        let mut string_next_state_repr_vec: Vec<String> = Vec::new();
-        for _m @ Straight(origin, dest) | _m @ Diagonal(origin, dest) in
-            &next_move_vec
-        {
-            let mut potential_state = state.clone();
-            potential_state.move_piece(*origin, *dest, true).unwrap();
-            string_next_state_repr_vec.push(potential_state.to_repr_string())
-        }
-
-        let next_state_nn_black_score: Vec<f64> = string_next_state_repr_vec
-            .into_iter()
-            .map(|e| match e.is_empty() {
-                false => self.run_python_nn(&e).unwrap(),
-                true => match state.current_turn {
-                    0 => 1.0,
-                    1 => 0.0,
-                    _ => {
-                        panic!("The current turn is a value other than 0 or 1.")
-                    }
-                },
+        let next_states: Vec<Snapshot> = next_move_vec
+            .iter()
+            .map(|&m| {
+                let undo = state.make_move(m);
+                let next_state = Snapshot::of(state);
+                state.unmake_move(undo);
+                next_state
             })
             .collect();
+        let next_state_nn_black_score =
+            self.model.evaluate_snapshots(&next_states);
 
         let enumerable_state_score =
             next_state_nn_black_score.iter().enumerate();
@@ -240,8 +341,67 @@ fn one_turn(&self, state: &mut State) {
     }
 }
 
+/// alpha-beta search like `AI`, with the network scoring the leaves instead
+/// of the heuristics. it takes a native model (a JSON export), since every
+/// leaf is a call into the network, which would enter python every time
+#[derive(Clone, Debug, PartialEq)]
+pub struct NNSearch {
+    name: String,
+    model: NnModel,
+    limit: SearchLimit,
+    search: SearchConfig,
+}
+
+impl NNSearch {
+    pub fn new(name: String, path: String, limit: SearchLimit) -> NNSearch {
+        let model = NnModel::load(&path).expect("could not load the NN model");
+        assert!(
+            matches!(model, NnModel::Native(_)),
+            "searching with {path} would run python for every leaf, export \
+             it to JSON first (see neuralnet/export.py)"
+        );
+        NNSearch {
+            name,
+            model,
+            limit,
+            search: SearchConfig::default(),
+        }
+    }
+
+    pub fn with_search_config(mut self, search: SearchConfig) -> NNSearch {
+        self.search = search;
+        self
+    }
+}
+
+impl Player for NNSearch {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn one_turn_with_rng(&self, state: &mut State, _rng: &mut dyn RngCore) {
+        let sugg_move =
+            Searcher::with_evaluator(Arc::new(self.model.clone()), self.search)
+                .search(state, &self.limit);
+
+        let (Move::Diagonal(origin, dest) | Move::Straight(origin, dest)) =
+            sugg_move.suggestion;
+
+        state
+            .move_piece(origin, dest, true)
+            .expect("could not play the NN-suggested move");
+
+        trace!("{sugg_move:#?}");
+    }
+}
+
 pub trait Player {
-    fn one_turn(&self, state: &mut State);
+    // plays a move, drawing whatever the player leaves to chance from `rng`
+    fn one_turn_with_rng(&self, state: &mut State, rng: &mut dyn RngCore);
+
+    fn one_turn(&self, state: &mut State) {
+        self.one_turn_with_rng(state, &mut rand::thread_rng())
+    }
 
     fn name(&self) -> String;
 }
@@ -251,6 +411,8 @@ pub enum PossiblePlayer {
     Person(Person),
     AI(AI),
     NN(NN),
+    MCTS(MCTS),
+    NNSearch(NNSearch),
 }
 
 impl Default for PossiblePlayer {
@@ -265,14 +427,18 @@ fn name(&self) -> String {
             PossiblePlayer::Person(p) => p.name(),
             PossiblePlayer::AI(a) => a.name(),
             PossiblePlayer::NN(n) => n.name(),
+            PossiblePlayer::MCTS(m) => m.name(),
+            PossiblePlayer::NNSearch(n) => n.name(),
         }
     }
 
-    fn one_turn(&self, state: &mut State) {
+    fn one_turn_with_rng(&self, state: &mut State, rng: &mut dyn RngCore) {
         match self {
-            PossiblePlayer::Person(p) => p.one_turn(state),
-            PossiblePlayer::AI(a) => a.one_turn(state),
-            PossiblePlayer::NN(n) => n.one_turn(state),
+            PossiblePlayer::Person(p) => p.one_turn_with_rng(state, rng),
+            PossiblePlayer::AI(a) => a.one_turn_with_rng(state, rng),
+            PossiblePlayer::NN(n) => n.one_turn_with_rng(state, rng),
+            PossiblePlayer::MCTS(m) => m.one_turn_with_rng(state, rng),
+            PossiblePlayer::NNSearch(n) => n.one_turn_with_rng(state, rng),
         }
     }
 }
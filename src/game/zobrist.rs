@@ -0,0 +1,32 @@
+//! zobrist keys for hashing milestone positions. a position's hash is the xor
+//! of the key of every (color, hex) that holds a piece, and of `SIDE_KEY` when
+//! it's white's turn
+
+use super::bitboard::NUM_HEXES;
+
+// splitmix64, so the keys are fixed at compile time and identical across runs
+const fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+const fn generate_piece_keys() -> [[u64; NUM_HEXES]; 2] {
+    let mut keys = [[0; NUM_HEXES]; 2];
+    let mut color = 0;
+    while color < 2 {
+        let mut hex = 0;
+        while hex < NUM_HEXES {
+            keys[color][hex] = splitmix64((color * NUM_HEXES + hex) as u64);
+            hex += 1;
+        }
+        color += 1;
+    }
+    keys
+}
+
+/// indexed by color (black is 0, white is 1) and then by hex
+pub const PIECE_KEYS: [[u64; NUM_HEXES]; 2] = generate_piece_keys();
+
+pub const SIDE_KEY: u64 = splitmix64(2 * NUM_HEXES as u64);
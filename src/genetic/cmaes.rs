@@ -0,0 +1,234 @@
+use log::info;
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+use super::config::ExperimentConfig;
+use super::optimizer::Optimizer;
+use super::seed::{self, Stream};
+use crate::ai::heuristics::{Weights, NUM_HEURISTICS};
+use crate::ai::tree::SearchLimit;
+use crate::game::player::AI;
+
+/// CMA-ES, following Hansen's "The CMA Evolution Strategy: A Tutorial". every
+/// batch is sampled from a normal distribution over the weights, whose mean,
+/// covariance and step size move toward the agents with the best elos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CmaEs {
+    mean: DVector<f64>,
+    sigma: f64,
+    cov: DMatrix<f64>,
+    // the evolution paths of the step size and of the covariance
+    p_sigma: DVector<f64>,
+    p_c: DVector<f64>,
+    generation: u32,
+    // the steps (before scaling by sigma) from the mean to the agents of the
+    // batch being played
+    steps: Vec<DVector<f64>>,
+    // these come from the config of the run, not the checkpoint
+    #[serde(skip)]
+    num_agents: usize,
+    #[serde(skip)]
+    agent_limit: SearchLimit,
+    #[serde(skip)]
+    seed: Option<u64>,
+}
+
+// the constants of an update, which only depend on the dimension and the
+// number of agents
+struct Parameters {
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    // the expected length of a standard normal vector
+    chi_n: f64,
+}
+
+impl Parameters {
+    fn new(lambda: usize) -> Parameters {
+        let n = NUM_HEURISTICS as f64;
+        let mu = (lambda / 2).max(1);
+
+        let raw: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / total).collect();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let d_sigma = 1.0
+            + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0)
+            + c_sigma;
+        let c_c = (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n);
+        let c_1 = 2.0 / ((n + 1.3).powi(2) + mu_eff);
+        let c_mu = (1.0 - c_1).min(
+            2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0).powi(2) + mu_eff),
+        );
+        let chi_n = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+
+        Parameters {
+            weights,
+            mu_eff,
+            c_sigma,
+            d_sigma,
+            c_c,
+            c_1,
+            c_mu,
+            chi_n,
+        }
+    }
+}
+
+impl CmaEs {
+    // starts from `mean` with a step size of cmaes_sigma, and no idea yet of
+    // how the weights depend on each other
+    pub fn new(mean: Weights, config: &ExperimentConfig) -> CmaEs {
+        CmaEs {
+            mean: DVector::from_row_slice(&mean),
+            sigma: config.cmaes_sigma,
+            cov: DMatrix::identity(NUM_HEURISTICS, NUM_HEURISTICS),
+            p_sigma: DVector::zeros(NUM_HEURISTICS),
+            p_c: DVector::zeros(NUM_HEURISTICS),
+            generation: 0,
+            steps: vec![],
+            num_agents: config.num_agents,
+            agent_limit: config.agent_limit,
+            seed: config.seed,
+        }
+    }
+
+    // the middle of the range random agents are drawn from
+    pub fn from_scratch(config: &ExperimentConfig) -> CmaEs {
+        CmaEs::new([0.5; NUM_HEURISTICS], config)
+    }
+
+    pub fn from_checkpoint(
+        checkpoint: &[u8],
+        config: &ExperimentConfig,
+    ) -> Option<CmaEs> {
+        let mut cmaes: CmaEs = bincode::deserialize(checkpoint).ok()?;
+        cmaes.num_agents = config.num_agents;
+        cmaes.agent_limit = config.agent_limit;
+        cmaes.seed = config.seed;
+        Some(cmaes)
+    }
+
+    fn agent(&self, step: &DVector<f64>) -> AI {
+        let x = &self.mean + self.sigma * step;
+        let mut weights = [0.0; NUM_HEURISTICS];
+        weights.copy_from_slice(x.as_slice());
+        AI::new(String::default(), weights, self.agent_limit)
+    }
+
+    // the covariance's eigenvectors, and the square roots of its
+    // eigenvalues (the scale along each)
+    fn decompose(&self) -> (DMatrix<f64>, DVector<f64>) {
+        let eigen = SymmetricEigen::new(self.cov.clone());
+        let scales = eigen.eigenvalues.map(|value| value.max(0.0).sqrt());
+        (eigen.eigenvectors, scales)
+    }
+}
+
+impl Optimizer for CmaEs {
+    fn ask(&mut self) -> Vec<AI> {
+        let (basis, scales) = self.decompose();
+        // generations are told apart by how many came before
+        let mut rng =
+            seed::rng(self.seed, Stream::Sampling, self.generation, 0);
+
+        self.steps = (0..self.num_agents)
+            .map(|_| {
+                let z = DVector::from_fn(NUM_HEURISTICS, |_, _| {
+                    rng.sample::<f64, _>(StandardNormal)
+                });
+                &basis * z.component_mul(&scales)
+            })
+            .collect();
+        self.steps.iter().map(|step| self.agent(step)).collect()
+    }
+
+    fn tell(&mut self, results: Vec<(i16, AI)>) {
+        let steps = std::mem::take(&mut self.steps);
+        assert_eq!(
+            results.len(),
+            steps.len(),
+            "every agent CMA-ES asked for needs a result"
+        );
+        let params = Parameters::new(steps.len());
+
+        // the best agents, weighted by rank
+        let mut ranked: Vec<usize> = (0..results.len()).collect();
+        ranked.sort_by_key(|&i| -results[i].0);
+        let selected: Vec<&DVector<f64>> = ranked
+            .iter()
+            .take(params.weights.len())
+            .map(|&i| &steps[i])
+            .collect();
+        let step = selected
+            .iter()
+            .zip(&params.weights)
+            .fold(DVector::zeros(NUM_HEURISTICS), |acc, (y, w)| acc + *y * *w);
+
+        self.mean += self.sigma * &step;
+
+        // C^(-1/2) * step
+        let (basis, scales) = self.decompose();
+        let whitened = &basis
+            * (basis.transpose() * &step)
+                .component_div(&scales.map(|scale| scale.max(f64::EPSILON)));
+
+        let c_sigma = params.c_sigma;
+        self.p_sigma = (1.0 - c_sigma) * &self.p_sigma
+            + (c_sigma * (2.0 - c_sigma) * params.mu_eff).sqrt() * whitened;
+
+        self.generation += 1;
+        let n = NUM_HEURISTICS as f64;
+        let p_sigma_norm = self.p_sigma.norm();
+        let h_sigma = p_sigma_norm
+            / (1.0 - (1.0 - c_sigma).powi(2 * self.generation as i32)).sqrt()
+            < (1.4 + 2.0 / (n + 1.0)) * params.chi_n;
+        let h_sigma = match h_sigma {
+            true => 1.0,
+            false => 0.0,
+        };
+
+        let c_c = params.c_c;
+        self.p_c = (1.0 - c_c) * &self.p_c
+            + h_sigma * (c_c * (2.0 - c_c) * params.mu_eff).sqrt() * &step;
+
+        let rank_mu = selected.iter().zip(&params.weights).fold(
+            DMatrix::zeros(NUM_HEURISTICS, NUM_HEURISTICS),
+            |acc, (y, w)| acc + *w * *y * y.transpose(),
+        );
+        let rank_one = &self.p_c * self.p_c.transpose()
+            + (1.0 - h_sigma) * c_c * (2.0 - c_c) * &self.cov;
+        self.cov = (1.0 - params.c_1 - params.c_mu) * &self.cov
+            + params.c_1 * rank_one
+            + params.c_mu * rank_mu;
+        // keeps rounding errors from making it asymmetric
+        self.cov = (&self.cov + self.cov.transpose()) * 0.5;
+
+        self.sigma *= ((c_sigma / params.d_sigma)
+            * (p_sigma_norm / params.chi_n - 1.0))
+            .exp();
+        info!(
+            "CMA-ES generation {} done, with a step size of {:.4}",
+            self.generation, self.sigma
+        );
+    }
+
+    // the mean of the distribution
+    fn best(&self) -> AI {
+        self.agent(&DVector::zeros(NUM_HEURISTICS))
+    }
+
+    fn checkpoint(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap()
+    }
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::crossover::Crossover;
+use super::optimizer::OptimizerKind;
+use crate::ai::search::SearchConfig;
+use crate::ai::tree::SearchLimit;
+
+/// how deep agents search when nothing says otherwise
+pub const DEFAULT_AGENT_LIMIT: SearchLimit = SearchLimit::Depth(4);
+
+/// everything a run of the genetic process depends on. it's read from a TOML
+/// or JSON file (see `ExperimentConfig::load`), where anything left out keeps
+/// its default, and can be overridden field by field from the command line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExperimentConfig {
+    // batches played every time the process is launched, and in all
+    pub per_num_batches: usize,
+    pub total_num_batches: usize,
+    pub num_agents: usize,
+    // matches per batch, each one game with either agent as black
+    pub num_matches: usize,
+    pub num_agents_retained: usize,
+    pub num_children_per_retained_agent: usize,
+    // children's weights are scaled by up to this much either way in the
+    // first batch, and perturb_decr times less every batch after
+    pub max_perturb_amt: f64,
+    pub perturb_decr: f64,
+    // how every agent searches
+    #[serde(with = "as_string")]
+    pub agent_limit: SearchLimit,
+    // the threads every agent searches a move with. batches already play
+    // their matches in parallel, so more than one is rarely worth it
+    pub search_threads: usize,
+    // how often a child is first crossed with another retained agent
+    pub crossover_rate: f64,
+    #[serde(with = "as_string")]
+    pub crossover: Crossover,
+    #[serde(with = "as_string")]
+    pub optimizer: OptimizerKind,
+    // the step size CMA-ES starts with
+    pub cmaes_sigma: f64,
+    // every random choice of a run is drawn from this, so runs with the same
+    // seed and config play out the same (unseeded runs don't)
+    pub seed: Option<u64>,
+}
+
+impl Default for ExperimentConfig {
+    fn default() -> Self {
+        ExperimentConfig {
+            per_num_batches: 10,
+            total_num_batches: 100,
+            num_agents: 36,
+            num_matches: 108,
+            num_agents_retained: 10,
+            num_children_per_retained_agent: 2,
+            max_perturb_amt: 0.1,
+            perturb_decr: 0.99,
+            agent_limit: DEFAULT_AGENT_LIMIT,
+            search_threads: 1,
+            crossover_rate: 0.0,
+            crossover: Crossover::Uniform,
+            optimizer: OptimizerKind::Genetic,
+            cmaes_sigma: 0.3,
+            seed: None,
+        }
+    }
+}
+
+impl ExperimentConfig {
+    // JSON files are read as JSON, anything else as TOML
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<ExperimentConfig> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        let contents = fs::read_to_string(&path)?;
+        let config: ExperimentConfig =
+            match path.as_ref().extension().is_some_and(|ext| ext == "json") {
+                true => serde_json::from_str(&contents)
+                    .map_err(|e| invalid(e.to_string()))?,
+                false => toml::from_str(&contents)
+                    .map_err(|e| invalid(e.to_string()))?,
+            };
+        config.validate().map_err(invalid)?;
+        Ok(config)
+    }
+
+    // set() overrides one field, given its name and value as it would be
+    // written in a config file (strings don't need quotes)
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let mut fields = match serde_json::to_value(&*self) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => unreachable!("the config is a struct"),
+        };
+        if !fields.contains_key(key) {
+            return Err(format!("there's no {key} in the config"));
+        }
+        let value = serde_json::from_str(value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+        fields.insert(key.to_string(), value);
+
+        *self = serde_json::from_value(serde_json::Value::Object(fields))
+            .map_err(|e| format!("couldn't set {key}: {e}"))?;
+        Ok(())
+    }
+
+    // validate() catches settings the genetic process can't run with
+    pub fn validate(&self) -> Result<(), String> {
+        let pairings = self.num_agents * self.num_agents.saturating_sub(1) / 2;
+        let children =
+            self.num_agents_retained * self.num_children_per_retained_agent;
+
+        if self.num_agents < 2 {
+            Err("there should be at least two agents".to_string())
+        } else if self.num_matches > pairings {
+            Err(format!(
+                "cannot schedule more matches ({}) than possible pairings of \
+                 agents ({pairings})",
+                self.num_matches
+            ))
+        } else if self.num_agents_retained == 0
+            || self.num_agents_retained > self.num_agents
+        {
+            Err(format!(
+                "between 1 and {} agents should be retained",
+                self.num_agents
+            ))
+        } else if children > self.num_agents {
+            Err(format!(
+                "the retained agents have more children ({children}) than \
+                 there are agents ({})",
+                self.num_agents
+            ))
+        } else if !(0.0..=1.0).contains(&self.crossover_rate) {
+            Err("the crossover rate should be between 0 and 1".to_string())
+        } else if !self.max_perturb_amt.is_finite()
+            || self.max_perturb_amt <= 0.0
+        {
+            Err("max_perturb_amt should be positive and finite".to_string())
+        } else if self.perturb_decr.is_nan()
+            || self.perturb_decr <= 0.0
+            || self.perturb_decr > 1.0
+        {
+            Err("perturb_decr should be above 0, and at most 1".to_string())
+        } else if self.cmaes_sigma.is_nan() || self.cmaes_sigma <= 0.0 {
+            Err("cmaes_sigma should be positive".to_string())
+        } else if self.agent_limit == SearchLimit::Depth(0) {
+            Err("agents should search at least one ply deep".to_string())
+        } else if self.search_threads == 0 {
+            Err("agents should search with at least one thread".to_string())
+        } else if self.seed.is_some_and(|seed| seed > i64::MAX as u64) {
+            // sqlite stores integers as i64
+            Err(format!("the seed should be at most {}", i64::MAX))
+        } else if self.seed.is_some() && self.search_threads > 1 {
+            Err(format!(
+                "a seeded run can't be replayed with agents searching on {} \
+                 threads, search on one or set seed=null",
+                self.search_threads
+            ))
+        } else if self.seed.is_some()
+            && matches!(self.agent_limit, SearchLimit::Time(_))
+        {
+            Err(format!(
+                "a seeded run can't be replayed with agents searching for a \
+                 time ({}), search to a depth or a number of iterations, or \
+                 set seed=null",
+                self.agent_limit
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    // search_config() is how agents search in the games of a batch: with
+    // the config's threads (rather than SEARCH_THREADS), and without the
+    // tablebase, which isn't part of the config
+    pub fn search_config(&self) -> SearchConfig {
+        SearchConfig {
+            threads: self.search_threads,
+            tablebase: false,
+            ..SearchConfig::default()
+        }
+    }
+
+    // the config as it's stored with the run in the database
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+// fields written the way they're parsed, like `depth:4` or `blx:0.5`
+mod as_string {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr<Err = String>,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
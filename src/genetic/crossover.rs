@@ -0,0 +1,77 @@
+use rand::Rng;
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ai::heuristics::Weights;
+
+/// how two parents' weights are recombined into a child's
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Crossover {
+    // every weight from one parent or the other, with even odds
+    Uniform,
+    // every weight a random mix of the parents' (somewhere between them)
+    Blend,
+    // every weight drawn uniformly from the parents' range, widened by alpha
+    // times its length on both sides, so children can leave the range the
+    // parents cover
+    BlxAlpha(f64),
+}
+
+impl Crossover {
+    pub fn cross<R: Rng>(
+        &self,
+        a: &Weights,
+        b: &Weights,
+        rng: &mut R,
+    ) -> Weights {
+        std::array::from_fn(|idx| {
+            let (x, y) = (a[idx], b[idx]);
+            match *self {
+                Crossover::Uniform => match rng.gen_bool(0.5) {
+                    true => x,
+                    false => y,
+                },
+                Crossover::Blend => {
+                    let t: f64 = rng.gen_range(0.0..=1.0);
+                    t * x + (1.0 - t) * y
+                }
+                Crossover::BlxAlpha(alpha) => {
+                    let (lo, hi) = (x.min(y), x.max(y));
+                    let margin = alpha * (hi - lo);
+                    rng.gen_range(lo - margin..=hi + margin)
+                }
+            }
+        })
+    }
+}
+
+// `uniform`, `blend`, `blx` (alpha 0.5) or `blx:<alpha>`
+impl FromStr for Crossover {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "uniform" => Ok(Crossover::Uniform),
+            None if s == "blend" => Ok(Crossover::Blend),
+            None if s == "blx" => Ok(Crossover::BlxAlpha(0.5)),
+            Some(("blx", alpha)) => match alpha.parse() {
+                Ok(alpha) if alpha >= 0.0 && f64::is_finite(alpha) => {
+                    Ok(Crossover::BlxAlpha(alpha))
+                }
+                _ => Err(format!("{alpha} isn't a valid alpha for blx")),
+            },
+            _ => Err(format!("unknown crossover {s}")),
+        }
+    }
+}
+
+impl fmt::Display for Crossover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Crossover::Uniform => write!(f, "uniform"),
+            Crossover::Blend => write!(f, "blend"),
+            Crossover::BlxAlpha(alpha) => write!(f, "blx:{alpha}"),
+        }
+    }
+}
@@ -1,205 +1,242 @@
-use std::env;
+use std::iter::zip;
 
+use super::config::ExperimentConfig;
+use super::optimizer::Optimizer;
 use super::referee::Referee;
-use crate::ai::tree::SearchLimit;
+use super::seed::{self, Stream};
 use crate::game::player::AI;
 use crate::{ai::heuristics::NUM_HEURISTICS, DATABASE_URL};
 use bincode::serialize;
 use chrono::Utc;
-use lazy_static::lazy_static;
 use log::{debug, info, warn};
+use rand::seq::SliceRandom;
 use rand::Rng;
 use rusqlite::{params, Connection, Result};
 
-lazy_static! {
-    static ref PER_NUM_BATCHES: usize =
-        env::var("PER_NUM_BATCHES").map_or(10, |elt| match elt.parse() {
-            Ok(i) => {
-                info!("Using PER_NUM_BATCHES environment variable ({})", i);
-                i
-            }
-            _ => 10,
-        });
-    static ref TOTAL_NUM_BATCHES: usize =
-    env::var("TOTAL_NUM_BATCHES").map_or(100, |elt| match elt.parse() {
-        Ok(i) => {
-            info!("Using TOTAL_NUM_BATCHES environment variable ({})", i);
-            i
-        }
-        _ => 100,
-    });
-    static ref NUM_AGENTS_RETAINED: usize = env::var("NUM_AGENTS_RETAINED")
-        .map_or(10, |elt| match elt.parse() {
-          Ok(i) => {
-                info!("Using NUM_AGENTS_RETAINED environment variable ({})", i);
-                i
-          },
-          _ => 10
-        });
-    static ref NUM_CHILDREN_PER_RETAINED_AGENT: usize =
-        env::var("NUM_CHILDREN_PER_RETAINED_AGENT")
-            .map_or(2, |elt| match elt.parse() {
-              Ok(i) => {
-                info!("Using NUM_CHILDREN_PER_RETAINED_AGENT environment variable ({})", i);
-                i
-              }
-              _ => 2
-            });
-    static ref MAX_PERTURB_AMT: f64 =
-        env::var("MAX_PERTURB_AMT").map_or(0.1, |elt| match elt.parse() {
-          Ok(i) => {
-            info!("Using MAX_PERTURB_AMT environment variable ({})", i);
-            i
-          }
-          _ => 0.1
-        });
-    static ref PERTURB_DECR: f64 =
-        env::var("PERTURB_DECR").map_or(0.1, |elt| match elt.parse() {
-          Ok(i) => {
-            info!("Using PERTURB_DECR environment variable ({})", i);
-            i
-          }
-          _ => 0.99
-        });
-    pub static ref NUM_AGENTS: usize =
-        env::var("NUM_AGENTS").map_or(36, |elt| match elt.parse() {
-          Ok(i) => {
-            info!("Using NUM_AGENTS environment variable ({})", i);
-            i
-          }
-          _ => 36
-        });
-    pub static ref NUM_MATCHES: usize =
-        env::var("NUM_MATCHES").map_or(108, |elt| match elt.parse() {
-          Ok(i) => {
-            info!("Using NUM_MATCHES environment variable ({})", i);
-            i
-          }
-          _ => 108
-        });
-    pub static ref AGENT_DEPTH: SearchLimit = env::var("AGENT_DEPTH")
-        .map_or(SearchLimit::Depth(4), |elt| SearchLimit::Depth(
-            match elt.parse() {
-              Ok(i) => {
-                info!("Using AGENT_DEPTH environment variable ({})", i);
-                i
-              }
-              _ => 4
-            }
-        ));
-}
-
-pub fn run(initial_batch_num: u32, initial_agents: Option<Vec<AI>>) -> AI {
+// run() plays batches of the optimizer's agents for an experiment, from
+// `initial_batch_num` until per_num_batches have been played (or
+// total_num_batches in all), and returns the best agent the optimizer found
+pub fn run(
+    experiment_id: i64,
+    initial_batch_num: u32,
+    mut optimizer: Box<dyn Optimizer>,
+    config: &ExperimentConfig,
+) -> AI {
     let mut total_batch_num = initial_batch_num;
     let mut process_batch_num = 1;
-    let agents = match initial_agents {
-        Some(i) => i,
-        None => {
-            let mut randomized_agents = vec![];
-            for _ in 0..*NUM_AGENTS {
-                randomized_agents.push(random_agent());
-            }
-            randomized_agents
-        }
-    };
-    let mut prev_batch = Referee::new(agents, total_batch_num);
 
-    while total_batch_num as usize <= *TOTAL_NUM_BATCHES
-        && process_batch_num <= *PER_NUM_BATCHES
+    while total_batch_num as usize <= config.total_num_batches
+        && process_batch_num <= config.per_num_batches
     {
-        prev_batch = run_one_batch(prev_batch);
+        run_one_batch(
+            optimizer.as_mut(),
+            experiment_id,
+            total_batch_num,
+            config,
+        );
         total_batch_num += 1;
         process_batch_num += 1;
     }
-    get_best_agents(prev_batch).first().unwrap().to_owned()
+    optimizer.best()
 }
 
-fn run_one_batch(mut prev: Referee) -> Referee {
-    let old_batch_num = prev.batch_num;
+fn run_one_batch(
+    optimizer: &mut dyn Optimizer,
+    experiment_id: i64,
+    batch_num: u32,
+    config: &ExperimentConfig,
+) {
+    let mut batch =
+        Referee::new(optimizer.ask(), batch_num, experiment_id, config);
     debug!(
-        "Running batch #{old_batch_num}/{} with agents: {:#.3?}",
-        *TOTAL_NUM_BATCHES, prev.agents
+        "Running batch #{batch_num}/{} with agents: {:#.3?}",
+        config.total_num_batches, batch.agents
     );
-    prev.play();
-    push_batch(&prev)
+    batch.play();
+
+    optimizer.tell(zip(batch.elos.clone(), batch.agents.clone()).collect());
+    push_batch(&batch, &optimizer.checkpoint(), config)
         .unwrap_or_else(|e| warn!("Could not push to recovery table: {e}"));
-    let old_best_agents = get_best_agents(prev);
-    info!("Batch #{old_batch_num} completed with best agents: {old_best_agents:#.3?}");
-    let new_agents = mutate(old_best_agents, old_batch_num);
+    info!(
+        "Batch #{batch_num} completed with best agent: {:#.3?}",
+        optimizer.best()
+    );
+}
+
+/// the genetic algorithm: the best num_agents_retained agents of every batch
+/// have num_children_per_retained_agent children each in the next, and
+/// random agents make up the rest
+pub struct Genetic {
+    agents: Vec<AI>,
+    best_agents: Vec<AI>,
+    batch_num: u32,
+    config: ExperimentConfig,
+}
 
-    Referee::new(new_agents, old_batch_num + 1)
+impl Genetic {
+    // starts from `initial_agents`, or random agents
+    pub fn new(
+        initial_batch_num: u32,
+        initial_agents: Option<Vec<AI>>,
+        config: &ExperimentConfig,
+    ) -> Genetic {
+        let agents = match initial_agents {
+            Some(i) => i,
+            None => {
+                let mut rng = seed::rng(
+                    config.seed,
+                    Stream::Agents,
+                    initial_batch_num,
+                    0,
+                );
+                let mut randomized_agents = vec![];
+                for _ in 0..config.num_agents {
+                    randomized_agents.push(random_agent(config, &mut rng));
+                }
+                randomized_agents
+            }
+        };
+        Genetic {
+            agents,
+            best_agents: vec![],
+            batch_num: initial_batch_num,
+            config: config.clone(),
+        }
+    }
 }
 
-fn checked_div(a: u32, b: u32) -> f32 {
-    if b == 0 {
-        0.0
-    } else {
-        a as f32 / b as f32
+impl Optimizer for Genetic {
+    fn ask(&mut self) -> Vec<AI> {
+        self.agents.clone()
+    }
+
+    fn tell(&mut self, results: Vec<(i16, AI)>) {
+        self.best_agents =
+            get_best_agents(results, self.config.num_agents_retained);
+        debug!(
+            "Batch #{} best agents: {:#.3?}",
+            self.batch_num, self.best_agents
+        );
+        self.agents =
+            mutate(self.best_agents.clone(), self.batch_num, &self.config);
+        self.batch_num += 1;
+    }
+
+    fn best(&self) -> AI {
+        match self.best_agents.first() {
+            Some(best) => best.to_owned(),
+            None => self.agents[0].to_owned(),
+        }
+    }
+
+    // the retained agents are the first of the batch in recovery_table, which
+    // is all it takes to carry on
+    fn checkpoint(&self) -> Vec<u8> {
+        vec![]
     }
 }
 
-fn get_best_agents(r: Referee) -> Vec<AI> {
-    let mut sorted_agents = r.get_agents_with_elos();
+fn get_best_agents(
+    mut sorted_agents: Vec<(i16, AI)>,
+    num_retained: usize,
+) -> Vec<AI> {
     sorted_agents.sort_by_key(|(elo, _ai)| *elo);
 
     sorted_agents.reverse();
 
     sorted_agents
         .into_iter()
-        .take(*NUM_AGENTS_RETAINED)
+        .take(num_retained)
         .map(|elt| elt.1)
         .collect::<Vec<AI>>()
 }
 
-fn children_from_agent(parent: AI, perturb_amt: f64) -> Vec<AI> {
+// children_from_agent() makes the children of `parent`, each first crossed
+// with another of the retained agents (crossover_rate of the time), then
+// mutated
+fn children_from_agent(
+    parent: &AI,
+    retained: &[AI],
+    perturb_amt: f64,
+    config: &ExperimentConfig,
+    rng: &mut impl Rng,
+) -> Vec<AI> {
     let mut children = vec![];
-    let mut rng = rand::thread_rng();
+    let partners: Vec<&AI> =
+        retained.iter().filter(|ai| *ai != parent).collect();
 
-    for _ in 0..*NUM_CHILDREN_PER_RETAINED_AGENT {
+    for _ in 0..config.num_children_per_retained_agent {
         let mut child_weights = parent.weights.to_owned();
+        if rng.gen_bool(config.crossover_rate) {
+            if let Some(partner) = partners.choose(rng) {
+                child_weights = config.crossover.cross(
+                    &child_weights,
+                    &partner.weights,
+                    rng,
+                );
+            }
+        }
+        // inclusive, so a perturbation that has decayed to nothing (or
+        // below what 1.0 can tell apart) leaves the weights as they are
         for (idx, w) in child_weights.into_iter().enumerate() {
             child_weights[idx] =
-                w * rng.gen_range(1.0 - perturb_amt..1.0 + perturb_amt)
+                w * rng.gen_range(1.0 - perturb_amt..=1.0 + perturb_amt)
         }
         children.push(AI::new(
             String::default(),
             child_weights,
-            AGENT_DEPTH.to_owned(),
+            config.agent_limit,
         ))
     }
 
     children
 }
 
-fn random_agent() -> AI {
+fn random_agent(config: &ExperimentConfig, rng: &mut impl Rng) -> AI {
     let mut weights = [1.0; NUM_HEURISTICS];
-    let mut rng = rand::thread_rng();
     for (idx, _) in weights.into_iter().enumerate() {
         weights[idx] = rng.gen_range(0.0..1.0);
     }
-    AI::new(String::default(), weights, AGENT_DEPTH.to_owned())
+    AI::new(String::default(), weights, config.agent_limit)
 }
 
-fn mutate(previous_best: Vec<AI>, time: u32) -> Vec<AI> {
+// mutate() makes the agents of the batch after `time` from the best of it
+fn mutate(
+    previous_best: Vec<AI>,
+    time: u32,
+    config: &ExperimentConfig,
+) -> Vec<AI> {
     let mut new_gen = vec![];
+    let mut rng = seed::rng(config.seed, Stream::Agents, time + 1, 0);
 
-    let perturb_amt = *MAX_PERTURB_AMT * PERTURB_DECR.powf((time - 1).into());
+    let perturb_amt =
+        config.max_perturb_amt * config.perturb_decr.powf((time - 1).into());
     info!(
         "Mutating children with {:.2}% perturbance",
         perturb_amt * 100.0
     );
-    for previous_agent in previous_best.into_iter() {
-        new_gen.append(&mut children_from_agent(previous_agent, perturb_amt));
+    for previous_agent in previous_best.iter() {
+        new_gen.append(&mut children_from_agent(
+            previous_agent,
+            &previous_best,
+            perturb_amt,
+            config,
+            &mut rng,
+        ));
     }
 
-    while new_gen.len() < *NUM_AGENTS {
-        new_gen.push(random_agent());
+    while new_gen.len() < config.num_agents {
+        new_gen.push(random_agent(config, &mut rng));
     }
 
     new_gen
 }
 
-fn push_batch(prev: &Referee) -> Result<()> {
+fn push_batch(
+    prev: &Referee,
+    checkpoint: &[u8],
+    config: &ExperimentConfig,
+) -> Result<()> {
     let conn = Connection::open(&*DATABASE_URL).unwrap();
 
     let agents_with_record: Vec<(&AI, &i16)> =
@@ -211,16 +248,31 @@ fn push_batch(prev: &Referee) -> Result<()> {
 
     conn.execute(
         r#"
-        INSERT INTO recovery_table (batch_id, agents, timestamp)
-        VALUES (?, ?, ?)
+        INSERT INTO recovery_table
+            (experiment_id, batch_id, agents, timestamp, optimizer, config)
+        VALUES (?, ?, ?, ?, ?, ?)
         "#,
-        params![batch_id, serialized_agents_with_record, timestamp],
+        params![
+            prev.experiment_id,
+            batch_id,
+            serialized_agents_with_record,
+            timestamp,
+            checkpoint,
+            config.to_json()
+        ],
     )?;
 
     Ok(())
 }
 
-pub fn mutate_from_recovery(batch_num: u32, agents: Vec<AI>) -> Vec<AI> {
-    let best_agents = agents[0..*NUM_AGENTS_RETAINED].to_vec();
-    mutate(best_agents, batch_num)
+pub fn mutate_from_recovery(
+    batch_num: u32,
+    agents: Vec<AI>,
+    config: &ExperimentConfig,
+) -> Vec<AI> {
+    let best_agents = agents
+        .into_iter()
+        .take(config.num_agents_retained)
+        .collect();
+    mutate(best_agents, batch_num, config)
 }
@@ -0,0 +1,353 @@
+use chrono::Utc;
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Result, Row};
+
+use super::config::ExperimentConfig;
+use super::seed::random_seed;
+use crate::ai::tree::SearchLimit;
+use crate::game::player::AI;
+
+/// the experiment the genetic process runs when none is named, which is also
+/// where batches from before experiments end up
+pub const DEFAULT_EXPERIMENT: &str = "default";
+
+/// the crate's version, and the commit it was built from (see build.rs)
+pub const CODE_VERSION: &str = env!("MILESTONE_VERSION");
+
+/// a named run of the genetic process: its batches in `recovery_table`, the
+/// games they played in `game_table`, and the config it was started with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Experiment {
+    pub id: i64,
+    pub name: String,
+    pub config: ExperimentConfig,
+    // what its randomness was seeded with, if it was
+    pub seed: Option<u64>,
+    // unknown for experiments from before they were recorded
+    pub code_version: Option<String>,
+    // the experiment this one was forked from
+    pub parent_id: Option<i64>,
+    pub created: String,
+}
+
+/// the last batch of an experiment, with its agents from best to worst elo
+#[derive(Debug, Clone)]
+pub struct Batch {
+    pub batch_num: u32,
+    pub agents: Vec<AI>,
+    // what the optimizer saved with the batch (see `Optimizer::checkpoint`)
+    pub checkpoint: Vec<u8>,
+}
+
+const COLUMNS: &str =
+    "experiment_id, name, config, seed, code_version, parent_id, created";
+
+impl Experiment {
+    // create() records a new experiment, run with `config` from this code,
+    // and seeded with a random seed if the config has none (unless agents
+    // search for a time or with several threads, which no seed can replay)
+    pub fn create(
+        conn: &Connection,
+        name: &str,
+        config: &ExperimentConfig,
+    ) -> Result<Experiment> {
+        let config = seeded(config);
+        Self::insert(conn, name, &config, Some(CODE_VERSION), None)
+    }
+
+    // fork() starts a new experiment from this one's last batch, which it
+    // carries on from with `config`
+    pub fn fork(
+        &self,
+        conn: &Connection,
+        name: &str,
+        config: &ExperimentConfig,
+    ) -> Result<Experiment> {
+        let fork = Self::insert(
+            conn,
+            name,
+            &seeded(config),
+            Some(CODE_VERSION),
+            Some(self.id),
+        )?;
+        conn.execute(
+            r#"
+            INSERT INTO recovery_table
+                (experiment_id, batch_id, agents, timestamp, optimizer, config)
+            SELECT ?1, batch_id, agents, timestamp, optimizer, config
+            FROM recovery_table WHERE experiment_id = ?2
+            ORDER BY batch_id DESC LIMIT 1
+            "#,
+            params![fork.id, self.id],
+        )?;
+        Ok(fork)
+    }
+
+    fn insert(
+        conn: &Connection,
+        name: &str,
+        config: &ExperimentConfig,
+        code_version: Option<&str>,
+        parent_id: Option<i64>,
+    ) -> Result<Experiment> {
+        let created = Utc::now().to_string();
+        conn.execute(
+            r#"
+            INSERT INTO experiments
+                (name, config, seed, code_version, parent_id, created)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            params![
+                name,
+                config.to_json(),
+                config.seed,
+                code_version,
+                parent_id,
+                created
+            ],
+        )?;
+        Ok(Experiment {
+            id: conn.last_insert_rowid(),
+            name: name.to_string(),
+            config: config.clone(),
+            seed: config.seed,
+            code_version: code_version.map(str::to_string),
+            parent_id,
+            created,
+        })
+    }
+
+    pub fn find(conn: &Connection, name: &str) -> Result<Option<Experiment>> {
+        conn.query_row(
+            &format!("SELECT {COLUMNS} FROM experiments WHERE name = ?1"),
+            [name],
+            Self::from_row,
+        )
+        .optional()
+    }
+
+    // every experiment, oldest first
+    pub fn list(conn: &Connection) -> Result<Vec<Experiment>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {COLUMNS} FROM experiments ORDER BY experiment_id"
+        ))?;
+        let experiments = stmt.query_map([], Self::from_row)?;
+        experiments.collect()
+    }
+
+    fn from_row(row: &Row) -> Result<Experiment> {
+        let config: String = row.get(2)?;
+        Ok(Experiment {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            config: serde_json::from_str(&config).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    Type::Text,
+                    e.into(),
+                )
+            })?,
+            seed: row.get(3)?,
+            code_version: row.get(4)?,
+            parent_id: row.get(5)?,
+            created: row.get(6)?,
+        })
+    }
+
+    pub fn num_batches(&self, conn: &Connection) -> Result<u32> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM recovery_table WHERE experiment_id = ?1",
+            [self.id],
+            |row| row.get(0),
+        )
+    }
+
+    // the batch to carry on from, if the experiment has played any
+    pub fn last_batch(&self, conn: &Connection) -> Result<Option<Batch>> {
+        conn.query_row(
+            r#"
+            SELECT batch_id, agents, optimizer FROM recovery_table
+            WHERE experiment_id = ?1
+            ORDER BY batch_id DESC LIMIT 1
+            "#,
+            [self.id],
+            |row| {
+                let bin_agents: Vec<u8> = row.get(1)?;
+                let checkpoint: Option<Vec<u8>> = row.get(2)?;
+                let mut agents_and_scores: Vec<(AI, i16)> =
+                    bincode::deserialize(&bin_agents).map_err(|e| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            1,
+                            Type::Blob,
+                            e,
+                        )
+                    })?;
+                agents_and_scores.sort_by_key(|(_, elo)| -elo);
+                Ok(Batch {
+                    batch_num: row.get(0)?,
+                    agents: agents_and_scores
+                        .into_iter()
+                        .map(|e| e.0)
+                        .collect(),
+                    checkpoint: checkpoint.unwrap_or_default(),
+                })
+            },
+        )
+        .optional()
+    }
+
+    // delete() removes the experiment along with its batches, games and
+    // their states
+    pub fn delete(self, conn: &mut Connection) -> Result<()> {
+        let tx = conn.transaction()?;
+        tx.execute(
+            r#"
+            DELETE FROM state_table WHERE game_id IN
+                (SELECT game_id FROM game_table WHERE experiment_id = ?1)
+            "#,
+            [self.id],
+        )?;
+        tx.execute(
+            "DELETE FROM game_table WHERE experiment_id = ?1",
+            [self.id],
+        )?;
+        tx.execute(
+            "DELETE FROM recovery_table WHERE experiment_id = ?1",
+            [self.id],
+        )?;
+        tx.execute(
+            "DELETE FROM experiments WHERE experiment_id = ?1",
+            [self.id],
+        )?;
+        tx.commit()
+    }
+}
+
+fn seeded(config: &ExperimentConfig) -> ExperimentConfig {
+    let mut config = config.clone();
+    if !matches!(config.agent_limit, SearchLimit::Time(_))
+        && config.search_threads == 1
+    {
+        config.seed.get_or_insert_with(random_seed);
+    }
+    config
+}
+
+// create_tables() creates `experiments` and `recovery_table`, once
+// `game_table` and `state_table` exist
+pub fn create_tables(conn: &mut Connection) -> Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS experiments (
+            experiment_id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            config TEXT NOT NULL,
+            seed INTEGER,
+            code_version TEXT,
+            parent_id INTEGER,
+            created DATETIME,
+            FOREIGN KEY(parent_id) REFERENCES experiments(experiment_id)
+        )
+        "#,
+        [],
+    )?;
+
+    // databases from before experiments have a recovery_table of batches
+    // alone, which is set aside to be copied over
+    let unscoped: bool = tx.query_row(
+        r#"
+        SELECT COUNT(*) > 0 FROM pragma_table_info('recovery_table')
+        WHERE NOT EXISTS (
+            SELECT * FROM pragma_table_info('recovery_table')
+            WHERE name = 'experiment_id'
+        )
+        "#,
+        [],
+        |row| row.get(0),
+    )?;
+    if unscoped {
+        tx.execute(
+            "ALTER TABLE recovery_table RENAME TO unscoped_recovery_table",
+            [],
+        )?;
+    }
+
+    tx.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS recovery_table (
+            experiment_id INTEGER NOT NULL,
+            batch_id INTEGER NOT NULL,
+            agents BLOB,
+            timestamp DATETIME,
+            optimizer BLOB,
+            config TEXT,
+            PRIMARY KEY(experiment_id, batch_id),
+            FOREIGN KEY(experiment_id) REFERENCES experiments(experiment_id)
+        )
+        "#,
+        [],
+    )?;
+
+    if unscoped {
+        scope_to_default_experiment(&tx)?;
+    }
+    tx.commit()
+}
+
+// the batches from before experiments, and the games of the genetic process
+// (the ones without a dataset), become the default experiment's
+fn scope_to_default_experiment(conn: &Connection) -> Result<()> {
+    // from before optimizers were checkpointed, or configs stored (fails if
+    // already added)
+    conn.execute(
+        "ALTER TABLE unscoped_recovery_table ADD COLUMN optimizer BLOB",
+        [],
+    )
+    .unwrap_or(0);
+    conn.execute(
+        "ALTER TABLE unscoped_recovery_table ADD COLUMN config TEXT",
+        [],
+    )
+    .unwrap_or(0);
+
+    let last_config: Option<String> = conn
+        .query_row(
+            r#"
+            SELECT config FROM unscoped_recovery_table
+            ORDER BY batch_id DESC LIMIT 1
+            "#,
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    let config = last_config
+        .and_then(|config| serde_json::from_str(&config).ok())
+        .unwrap_or_default();
+    let experiment = match Experiment::find(conn, DEFAULT_EXPERIMENT)? {
+        Some(experiment) => experiment,
+        None => {
+            Experiment::insert(conn, DEFAULT_EXPERIMENT, &config, None, None)?
+        }
+    };
+
+    conn.execute(
+        r#"
+        INSERT INTO recovery_table
+            (experiment_id, batch_id, agents, timestamp, optimizer, config)
+        SELECT ?1, batch_id, agents, timestamp, optimizer, config
+        FROM unscoped_recovery_table
+        "#,
+        [experiment.id],
+    )?;
+    conn.execute(
+        r#"
+        UPDATE game_table SET experiment_id = ?1
+        WHERE experiment_id IS NULL AND dataset IS NULL
+        "#,
+        [experiment.id],
+    )?;
+    conn.execute("DROP TABLE unscoped_recovery_table", [])?;
+    Ok(())
+}
@@ -1,7 +1,22 @@
+mod cmaes;
+mod config;
+mod crossover;
 mod emperor;
+mod experiment;
+mod optimizer;
 mod referee;
+pub mod seed;
+mod selfplay;
 
+pub use cmaes::CmaEs;
+pub use config::{ExperimentConfig, DEFAULT_AGENT_LIMIT};
+pub use crossover::Crossover;
 pub use emperor::mutate_from_recovery;
 pub use emperor::run;
-pub use emperor::AGENT_DEPTH;
+pub use emperor::Genetic;
+pub use experiment::{create_tables, Batch, Experiment};
+pub use experiment::{CODE_VERSION, DEFAULT_EXPERIMENT};
+pub use optimizer::{Optimizer, OptimizerKind};
+pub use referee::Referee;
 pub use referee::Score;
+pub use selfplay::{Noise, SelfPlay};
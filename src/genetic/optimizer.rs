@@ -0,0 +1,54 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::game::player::AI;
+
+/// searches for good weights, one batch of agents at a time. every batch
+/// plays its matches through a `Referee`, and the optimizer learns from the
+/// agents' elos before proposing the next batch
+pub trait Optimizer {
+    // the agents to play the next batch
+    fn ask(&mut self) -> Vec<AI>;
+
+    // how the agents from ask() did, with their elos, in the same order
+    fn tell(&mut self, results: Vec<(i16, AI)>);
+
+    // the optimizer's best guess so far
+    fn best(&self) -> AI;
+
+    // whatever the optimizer needs to carry on from here besides the last
+    // batch's agents, saved with the batch in `recovery_table`
+    fn checkpoint(&self) -> Vec<u8>;
+}
+
+/// the optimizers the genetic process can run with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OptimizerKind {
+    // truncation selection, with mutation and optional crossover (see
+    // `emperor`)
+    #[default]
+    Genetic,
+    // the covariance matrix adaptation evolution strategy (see `cmaes`)
+    CmaEs,
+}
+
+impl FromStr for OptimizerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ga" | "genetic" => Ok(OptimizerKind::Genetic),
+            "cmaes" | "cma-es" => Ok(OptimizerKind::CmaEs),
+            _ => Err(format!("unknown optimizer {s}")),
+        }
+    }
+}
+
+impl fmt::Display for OptimizerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptimizerKind::Genetic => write!(f, "ga"),
+            OptimizerKind::CmaEs => write!(f, "cmaes"),
+        }
+    }
+}
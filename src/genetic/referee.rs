@@ -1,6 +1,6 @@
 use log::{debug, info};
 use rand::seq::SliceRandom;
-use rand::{thread_rng, Rng};
+use rand::{Rng, RngCore};
 use rayon::prelude::*;
 
 use crate::game::{
@@ -8,7 +8,10 @@
     player::{PossiblePlayer, AI},
 };
 
-use super::emperor::{NUM_AGENTS, NUM_MATCHES};
+use super::config::ExperimentConfig;
+use super::seed::{self, Stream};
+use crate::DATABASE_URL;
+use rusqlite::Connection;
 use std::time::Instant;
 use std::{collections::HashSet, iter::zip};
 
@@ -17,40 +20,67 @@ pub struct Referee {
     results: Vec<Score>,
     pub elos: Vec<i16>,
     pub batch_num: u32,
+    // the experiment the batch's games are recorded with
+    pub experiment_id: i64,
+    num_matches: usize,
+    seed: Option<u64>,
 }
 
 pub type Score = (u32, u32);
 
 impl Referee {
-    pub fn new(agents: Vec<AI>, batch_num: u32) -> Referee {
+    pub fn new(
+        agents: Vec<AI>,
+        batch_num: u32,
+        experiment_id: i64,
+        config: &ExperimentConfig,
+    ) -> Referee {
+        assert_eq!(
+            agents.len(),
+            config.num_agents,
+            "a batch should have num_agents agents"
+        );
+        // agents search only with what the config records (see
+        // ExperimentConfig::search_config)
+        let search = config.search_config();
         Referee {
-            agents,
-            results: vec![(0, 0); *NUM_AGENTS],
-            elos: vec![1000; *NUM_AGENTS],
+            agents: agents
+                .into_iter()
+                .map(|agent| agent.with_search_config(search))
+                .collect(),
+            results: vec![(0, 0); config.num_agents],
+            elos: vec![1000; config.num_agents],
             batch_num,
+            experiment_id,
+            num_matches: config.num_matches,
+            seed: config.seed,
         }
     }
 
-    fn generate_matches(&self) -> HashSet<(usize, usize)> {
-        let possible_matches = n_choose_r(*NUM_AGENTS, 2);
-        if *NUM_MATCHES > possible_matches {
+    // generate_matches() schedules the batch's matches, in the order their
+    // results are counted
+    fn generate_matches(&self) -> Vec<(usize, usize)> {
+        let num_agents = self.agents.len();
+        let possible_matches = n_choose_r(num_agents, 2);
+        if self.num_matches > possible_matches {
             panic!(
                 "cannot schedule more matches ({}) than possible pairings of agents ({})",
-                *NUM_MATCHES, possible_matches
+                self.num_matches, possible_matches
             );
         }
 
-        let mut matches = HashSet::new();
-        let mut rng = rand::thread_rng();
+        let mut matches = vec![];
+        let mut scheduled = HashSet::new();
+        let mut rng = seed::rng(self.seed, Stream::Schedule, self.batch_num, 0);
         let mut num_matches_created = 0;
 
         let mut agent_turn = 0;
-        let mut agents = (0..*NUM_AGENTS).collect::<Vec<usize>>();
-        agents.shuffle(&mut thread_rng());
+        let mut agents = (0..num_agents).collect::<Vec<usize>>();
+        agents.shuffle(&mut rng);
 
-        while num_matches_created < *NUM_MATCHES {
-            let agent_index = agents[agent_turn % *NUM_AGENTS];
-            let opponent_agent_index = rng.gen_range(0..*NUM_AGENTS);
+        while num_matches_created < self.num_matches {
+            let agent_index = agents[agent_turn % num_agents];
+            let opponent_agent_index = rng.gen_range(0..num_agents);
 
             let m: (usize, usize) = (
                 std::cmp::min(agent_index, opponent_agent_index),
@@ -58,8 +88,8 @@ fn generate_matches(&self) -> HashSet<(usize, usize)> {
             );
 
             agent_turn += 1;
-            if agent_index != opponent_agent_index && !matches.contains(&m) {
-                matches.insert(m);
+            if agent_index != opponent_agent_index && scheduled.insert(m) {
+                matches.push(m);
                 num_matches_created += 1;
             }
         }
@@ -70,16 +100,21 @@ fn generate_matches(&self) -> HashSet<(usize, usize)> {
     }
 
     pub fn play(&mut self) {
-        let matches = Vec::from_iter(self.generate_matches());
+        let matches = self.generate_matches();
 
         info!("Playing games in batch #{}", self.batch_num);
         let results: Vec<(&usize, u8, &usize, u8)> = matches
             .par_iter()
-            .map(|(agent_one_idx, agent_two_idx)| {
+            .enumerate()
+            .map(|(i, (agent_one_idx, agent_two_idx))| {
                 let before = Instant::now();
-                let (agent_one_wins, agent_two_wins) = Self::play_one_match(
-                    &self.agents[*agent_one_idx],
-                    &self.agents[*agent_two_idx],
+                let mut rng =
+                    seed::rng(self.seed, Stream::Match, self.batch_num, i as u64);
+                let (agent_one_wins, agent_two_wins) = Self::play_match(
+                    &PossiblePlayer::AI(self.agents[*agent_one_idx].to_owned()),
+                    &PossiblePlayer::AI(self.agents[*agent_two_idx].to_owned()),
+                    Some(self.experiment_id),
+                    &mut rng,
                 );
                 debug!(
                     "Played a match between {} and {} in {:.2?}, {:?} is the result. (Batch {})",
@@ -99,7 +134,7 @@ pub fn play(&mut self) {
             self.results[*a1_idx].1 += 2;
             self.results[*a2_idx].0 += a2_w as u32;
             self.results[*a2_idx].1 += 2;
-            update_elo(self, a1_idx, a2_idx, i16::try_from(a1_w).unwrap(), 30);
+            update_elo(self, a1_idx, a2_idx, i16::from(a1_w), 30);
         }
 
         debug!(
@@ -114,25 +149,42 @@ pub fn play(&mut self) {
         debug!("Elos of batch {}: {:?}", self.batch_num, self.elos);
     }
 
-    fn play_one_match(agent_one: &AI, agent_two: &AI) -> (u8, u8) {
+    // plays two games, one with each player as black, and returns how many
+    // each player won. any kind of player can play, so other searches can be
+    // compared with the heuristic AI
+    pub fn play_one_match(
+        agent_one: &PossiblePlayer,
+        agent_two: &PossiblePlayer,
+    ) -> (u8, u8) {
+        Self::play_match(agent_one, agent_two, None, &mut rand::thread_rng())
+    }
+
+    // the games of a match are recorded with the experiment it's played for,
+    // if there is one, and the players' random choices are drawn from `rng`
+    fn play_match(
+        agent_one: &PossiblePlayer,
+        agent_two: &PossiblePlayer,
+        experiment_id: Option<i64>,
+        rng: &mut dyn RngCore,
+    ) -> (u8, u8) {
         let game_one = GameBuilder::new()
-            .set_player_1(PossiblePlayer::AI(agent_one.to_owned()))
-            .set_player_2(PossiblePlayer::AI(agent_two.to_owned()))
+            .set_player_1(agent_one.to_owned())
+            .set_player_2(agent_two.to_owned())
             .build();
         let game_two = GameBuilder::new()
-            .set_player_1(PossiblePlayer::AI(agent_two.to_owned()))
-            .set_player_2(PossiblePlayer::AI(agent_one.to_owned()))
+            .set_player_1(agent_two.to_owned())
+            .set_player_2(agent_one.to_owned())
             .build();
 
         let mut agent_one_wins: u8 = 0;
         let mut agent_two_wins: u8 = 0;
-        match Self::play_one_game(game_one) {
+        match Self::play_one_game(game_one, experiment_id, rng) {
             Some(0) => agent_one_wins += 1,
             Some(1) => agent_two_wins += 1,
             None => (),
             _ => panic!("impossible winner (there are only two players)"),
         }
-        match Self::play_one_game(game_two) {
+        match Self::play_one_game(game_two, experiment_id, rng) {
             Some(0) => agent_two_wins += 1,
             Some(1) => agent_one_wins += 1,
             None => (),
@@ -142,15 +194,26 @@ fn play_one_match(agent_one: &AI, agent_two: &AI) -> (u8, u8) {
         (agent_one_wins, agent_two_wins)
     }
 
-    fn play_one_game(mut game: State) -> Option<u8> {
+    fn play_one_game(
+        mut game: State,
+        experiment_id: Option<i64>,
+        rng: &mut dyn RngCore,
+    ) -> Option<u8> {
         while game.active {
             game.add_to_state_history();
-            game.play_one_turn();
+            game.play_one_turn_with_rng(rng);
+        }
+        match experiment_id {
+            Some(id) => {
+                let mut conn = Connection::open(&*DATABASE_URL).unwrap();
+                game.push_game_to_experiment(&mut conn, id).unwrap();
+            }
+            None => game.push_game_and_state().unwrap(),
         }
-        game.push_game_and_state().unwrap();
         game.winner
     }
 
+    #[allow(dead_code)]
     pub fn get_agents_with_results(self) -> Vec<(Score, AI)> {
         zip(self.results, self.agents).collect::<Vec<(Score, AI)>>()
     }
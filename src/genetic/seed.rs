@@ -0,0 +1,42 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// what a stream of random numbers is drawn for, so no two uses of the same
+/// seed draw the same numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    // the agents of a batch, random or mutated
+    Agents = 1,
+    // which agents play each other in a batch
+    Schedule = 2,
+    // whatever the players of one match leave to chance
+    Match = 3,
+    // the agents CMA-ES samples for a generation
+    Sampling = 4,
+}
+
+// rng() is the random number generator for one stream of a batch (and of
+// one of its matches, or 0), which only depends on the run's seed and on
+// where it's used, and not on what's been drawn elsewhere or the order
+// threads run in. unseeded runs get one seeded from entropy
+pub fn rng(
+    seed: Option<u64>,
+    stream: Stream,
+    batch_num: u32,
+    index: u64,
+) -> ChaCha8Rng {
+    let Some(seed) = seed else {
+        return ChaCha8Rng::from_entropy();
+    };
+    let mut key = [0; 32];
+    key[0..8].copy_from_slice(&seed.to_le_bytes());
+    key[8..16].copy_from_slice(&(stream as u64).to_le_bytes());
+    key[16..24].copy_from_slice(&u64::from(batch_num).to_le_bytes());
+    key[24..32].copy_from_slice(&index.to_le_bytes());
+    ChaCha8Rng::from_seed(key)
+}
+
+// a seed for a new experiment (small enough for TOML's integers)
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen_range(0..i64::MAX as u64)
+}
@@ -0,0 +1,180 @@
+use log::info;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rayon::prelude::*;
+use rusqlite::{Connection, Result};
+
+use std::str::FromStr;
+
+use crate::ai::heuristics::{HeuristicWeights, NUM_HEURISTICS};
+use crate::game::board::Move::{self, Diagonal, Straight};
+use crate::game::gamestate::{GameBuilder, State};
+use crate::game::player::PossiblePlayer;
+
+/// how the players' moves are sometimes replaced, so self-play games don't
+/// all look alike
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Noise {
+    None,
+    // a uniformly random move, this often
+    Epsilon(f64),
+    // every move, drawn with probability proportional to
+    // exp(score / temperature), where the score is the heuristic evaluation
+    // of the position it leads to (for the player moving)
+    Temperature(f64),
+}
+
+// `none`, `epsilon:<chance of a random move>` (between 0 and 1) or
+// `temperature:<temperature>` (above 0)
+impl FromStr for Noise {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split(':').collect::<Vec<&str>>()[..] {
+            ["none"] => Ok(Noise::None),
+            ["epsilon", e] => match e.parse() {
+                Ok(e) if (0.0..=1.0).contains(&e) => Ok(Noise::Epsilon(e)),
+                _ => Err(format!("{s} isn't a chance between 0 and 1")),
+            },
+            ["temperature", t] => match t.parse() {
+                Ok(t) if t > 0.0 && f64::is_finite(t) => {
+                    Ok(Noise::Temperature(t))
+                }
+                _ => Err(format!("{s} isn't a temperature above 0")),
+            },
+            _ => Err(format!("{s} isn't a valid noise")),
+        }
+    }
+}
+
+/// plays games between two players, switching colors every game, and
+/// records them under a dataset tag
+#[derive(Debug, Clone)]
+pub struct SelfPlay {
+    pub players: [PossiblePlayer; 2],
+    pub games: usize,
+    pub noise: Noise,
+    // only the first this many plies of each game are noisy
+    pub noise_plies: usize,
+    pub dataset: String,
+}
+
+impl SelfPlay {
+    pub fn new(
+        players: [PossiblePlayer; 2],
+        games: usize,
+        dataset: String,
+    ) -> SelfPlay {
+        SelfPlay {
+            players,
+            games,
+            noise: Noise::None,
+            noise_plies: usize::MAX,
+            dataset,
+        }
+    }
+
+    pub fn with_noise(mut self, noise: Noise, noise_plies: usize) -> SelfPlay {
+        self.noise = noise;
+        self.noise_plies = noise_plies;
+        self
+    }
+
+    // run() plays every game in parallel, then writes them to the
+    // database. returns how many games black won
+    pub fn run(&self, conn: &mut Connection) -> Result<usize> {
+        let games: Vec<State> = (0..self.games)
+            .into_par_iter()
+            .map(|i| self.play_one_game(i))
+            .collect();
+
+        for game in &games {
+            game.push_game_to_dataset(conn, &self.dataset)?;
+        }
+
+        let black_wins =
+            games.iter().filter(|game| game.winner == Some(0)).count();
+        info!(
+            "Played {} self-play games for dataset {} (black won {black_wins})",
+            self.games, self.dataset
+        );
+        Ok(black_wins)
+    }
+
+    fn play_one_game(&self, i: usize) -> State {
+        let [one, two] = self.players.clone();
+        let (black, white) = match i % 2 {
+            0 => (one, two),
+            _ => (two, one),
+        };
+        let mut game = GameBuilder::new()
+            .set_player_1(black)
+            .set_player_2(white)
+            .build();
+
+        let heuristics = HeuristicWeights::new([1.0; NUM_HEURISTICS]);
+        let mut rng = rand::thread_rng();
+        let mut ply = 0;
+        while game.active {
+            game.add_to_state_history();
+            let noisy_move = match ply < self.noise_plies {
+                true => {
+                    noisy_move(&mut game, self.noise, &heuristics, &mut rng)
+                }
+                false => None,
+            };
+            match noisy_move {
+                Some(Diagonal(from, to) | Straight(from, to)) => game
+                    .move_piece(from, to, true)
+                    .expect("could not play the noisy move"),
+                None => game.play_one_turn(),
+            }
+            ply += 1;
+        }
+        game
+    }
+}
+
+// the move to play instead of the player's, if any
+fn noisy_move<R: Rng>(
+    state: &mut State,
+    noise: Noise,
+    heuristics: &HeuristicWeights,
+    rng: &mut R,
+) -> Option<Move> {
+    let moves = state.current_possible_moves(state.current_turn);
+    match noise {
+        Noise::None => None,
+        Noise::Epsilon(epsilon) => match rng.gen_bool(epsilon) {
+            true => moves.choose(rng).copied(),
+            false => None,
+        },
+        Noise::Temperature(temperature) => {
+            let sign = match state.current_turn {
+                0 => 1.0,
+                _ => -1.0,
+            };
+            let scores: Vec<f64> = moves
+                .iter()
+                .map(|&m| {
+                    let undo = state.make_move(m);
+                    let score = sign * heuristics.score(state);
+                    state.unmake_move(undo);
+                    score
+                })
+                .collect();
+            // shifted by the best score so nothing overflows (a win's score
+            // is f64::MAX, which leaves only the wins)
+            let best = scores.iter().copied().fold(f64::MIN, f64::max);
+            let moves_and_weights: Vec<(Move, f64)> = moves
+                .iter()
+                .zip(scores)
+                .map(|(&m, score)| (m, ((score - best) / temperature).exp()))
+                .collect();
+            moves_and_weights
+                .choose_weighted(rng, |(_, weight)| *weight)
+                .ok()
+                .map(|(m, _)| *m)
+        }
+    }
+}
@@ -1,5 +1,5 @@
 
-use genetic::AGENT_DEPTH;
+use genetic::DEFAULT_AGENT_LIMIT;
 use lazy_static::lazy_static;
 
 
@@ -8,7 +8,6 @@
 
 
 
-use rayon::prelude::*;
 use std::env;
 
 
@@ -39,14 +38,14 @@ fn play_two_ai(weights_one: &String, weights_two: &String) -> u8 {
             floats_from_string(weights_one.to_string())
                 .try_into()
                 .unwrap(),
-            *AGENT_DEPTH,
+            DEFAULT_AGENT_LIMIT,
         )))
         .set_player_2(PossiblePlayer::AI(AI::new(
             "AI 2".to_string(),
             floats_from_string(weights_two.to_string())
                 .try_into()
                 .unwrap(),
-            *AGENT_DEPTH,
+            DEFAULT_AGENT_LIMIT,
         )))
         .build();
 
@@ -56,14 +55,14 @@ fn play_two_ai(weights_one: &String, weights_two: &String) -> u8 {
             floats_from_string(weights_one.to_string())
                 .try_into()
                 .unwrap(),
-            *AGENT_DEPTH,
+            DEFAULT_AGENT_LIMIT,
         )))
         .set_player_1(PossiblePlayer::AI(AI::new(
             "AI 2".to_string(),
             floats_from_string(weights_two.to_string())
                 .try_into()
                 .unwrap(),
-            *AGENT_DEPTH,
+            DEFAULT_AGENT_LIMIT,
         )))
         .build();
 
@@ -106,7 +105,7 @@ pub fn play_ai_vs_nn(weights: &str, file_path: &str, in_order: bool) -> u8 {
             PossiblePlayer::AI(AI::new(
                 "AI 1".to_string(),
                 floats_from_string(weights.to_string()).try_into().unwrap(),
-                *AGENT_DEPTH,
+                DEFAULT_AGENT_LIMIT,
             )),
             PossiblePlayer::NN(NN::new(
                 "NN 2".to_string(),
@@ -122,7 +121,7 @@ pub fn play_ai_vs_nn(weights: &str, file_path: &str, in_order: bool) -> u8 {
             PossiblePlayer::AI(AI::new(
                 "AI 2".to_string(),
                 floats_from_string(weights.to_string()).try_into().unwrap(),
-                *AGENT_DEPTH,
+                DEFAULT_AGENT_LIMIT,
             )),
         )
     };
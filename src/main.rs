@@ -44,20 +44,30 @@ fn main() {
 fn normal_milestone() {
     info!("STARTING MILESTONE PROCESS");
 
-    let conn = Connection::open(&*DATABASE_URL).unwrap();
+    let mut conn = Connection::open(&*DATABASE_URL).unwrap();
 
     // Create a table called `game_table`
     conn.execute(
         r#"
         CREATE TABLE IF NOT EXISTS game_table (
             game_id INTEGER PRIMARY KEY,
-            result INTEGER
+            result INTEGER,
+            dataset TEXT,
+            experiment_id INTEGER,
+            FOREIGN KEY(experiment_id) REFERENCES experiments(experiment_id)
         )
         "#,
         [],
     )
     .unwrap_or(0);
 
+    // databases from before datasets were tagged (fails if already added)
+    conn.execute("ALTER TABLE game_table ADD COLUMN dataset TEXT", [])
+        .unwrap_or(0);
+    // and before games were scoped to experiments
+    conn.execute("ALTER TABLE game_table ADD COLUMN experiment_id INTEGER", [])
+        .unwrap_or(0);
+
     // Create a table called `state_table`
     conn.execute(
         r#"
@@ -73,25 +83,18 @@ fn normal_milestone() {
     )
     .unwrap_or(0);
 
-    // Create a table called `recovery_table`
-    conn.execute(
-        r#"
-        CREATE TABLE IF NOT EXISTS recovery_table (
-            batch_id INTEGER PRIMARY KEY,
-            agents BLOB,
-            timestamp DATETIME
-        )
-        "#,
-        [],
-    )
-    .unwrap_or(0);
+    // Create the tables called `experiments` and `recovery_table`
+    genetic::create_tables(&mut conn).unwrap();
 
-    conn.close();
+    conn.close().unwrap();
 
     let args: Vec<String> = env::args().collect();
     if args.len() > 1 {
+        if cli::run_command(&args[1..]) {
+            return;
+        }
         info!("Argument passed. Starting the genetic process");
-        cli::start_genetic_process()
+        cli::run_experiment(&args[1..])
     } else {
         cli::choose_phase()
     }
@@ -0,0 +1,78 @@
+use milestone::ai::book::{BookSelection, OpeningBook};
+use milestone::game::gamestate::{GameBuilder, State};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rusqlite::{params, Connection};
+
+// a book of two games, one won by each side, that both always played the
+// first possible move
+fn book(max_plies: u8) -> OpeningBook {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        r#"
+        CREATE TABLE game_table (game_id INTEGER PRIMARY KEY, result INTEGER);
+        CREATE TABLE state_table (
+            state_id INTEGER PRIMARY KEY,
+            state TEXT NOT NULL,
+            move_number INTEGER,
+            game_id INTEGER
+        );
+        INSERT INTO game_table (game_id, result) VALUES (1, 0), (2, 1);
+        INSERT INTO state_table (state, move_number, game_id) VALUES ('', 0, 1);
+        "#,
+    )
+    .unwrap();
+
+    let mut state = GameBuilder::new().build();
+    for ply in 0..4 {
+        conn.execute(
+            r#"
+            INSERT INTO state_table (state, move_number, game_id)
+            VALUES (?1, ?2, 1), (?1, ?2, 2)
+            "#,
+            params![state.to_repr_string(), ply],
+        )
+        .unwrap();
+        play_first_move(&mut state);
+    }
+    OpeningBook::build(&conn, max_plies, 1).unwrap()
+}
+
+fn play_first_move(state: &mut State) {
+    let m = state.current_possible_moves(state.current_turn)[0];
+    state.make_move(m);
+}
+
+#[test]
+fn the_book_only_plays_the_plies_it_covers() {
+    let book = book(2);
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut state = GameBuilder::new().build();
+    let first = state.current_possible_moves(0)[0];
+
+    let probe = |state: &mut State, selection, rng: &mut StdRng| {
+        book.probe(state, selection, rng).map(|m| m.mv)
+    };
+    assert_eq!(
+        probe(&mut state, BookSelection::Best, &mut rng),
+        Some(first)
+    );
+    assert_eq!(probe(&mut state, BookSelection::Off, &mut rng), None);
+
+    play_first_move(&mut state);
+    assert!(probe(&mut state, BookSelection::Best, &mut rng).is_some());
+
+    // a position the book knows, but reached past the plies it covers
+    let mut late = GameBuilder::new().build();
+    late.plies = 2;
+    assert_eq!(probe(&mut late, BookSelection::Best, &mut rng), None);
+}
+
+#[test]
+fn book_selections_parse_by_name() {
+    assert_eq!("off".parse(), Ok(BookSelection::Off));
+    assert_eq!("best".parse(), Ok(BookSelection::Best));
+    assert_eq!("weighted".parse(), Ok(BookSelection::Weighted));
+    assert!("wieghted".parse::<BookSelection>().is_err());
+    assert_eq!(BookSelection::default(), BookSelection::Off);
+}
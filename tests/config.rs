@@ -0,0 +1,80 @@
+use milestone::ai::tree::SearchLimit;
+use milestone::genetic::{ExperimentConfig, OptimizerKind};
+
+use std::time::Duration;
+
+#[test]
+fn missing_fields_keep_their_defaults() {
+    let config: ExperimentConfig =
+        toml::from_str("num_agents = 20\nnum_matches = 60").unwrap();
+
+    assert_eq!(config.num_agents, 20);
+    assert_eq!(config.num_matches, 60);
+    assert_eq!(
+        config.perturb_decr,
+        ExperimentConfig::default().perturb_decr
+    );
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    assert!(toml::from_str::<ExperimentConfig>("num_agent = 12").is_err());
+}
+
+#[test]
+fn overrides_parse_like_the_file() {
+    let mut config = ExperimentConfig::default();
+    config.set("agent_limit", "time:500").unwrap();
+    config.set("optimizer", "cmaes").unwrap();
+    config.set("max_perturb_amt", "0.2").unwrap();
+
+    assert_eq!(
+        config.agent_limit,
+        SearchLimit::Time(Duration::from_millis(500))
+    );
+    assert_eq!(config.optimizer, OptimizerKind::CmaEs);
+    assert_eq!(config.max_perturb_amt, 0.2);
+
+    assert!(config.set("perturb", "0.2").is_err());
+    assert!(config.set("num_agents", "many").is_err());
+    for alpha in ["blx:-0.5", "blx:inf", "blx:NaN"] {
+        assert!(config.set("crossover", alpha).is_err(), "{alpha} parsed");
+    }
+}
+
+#[test]
+fn stored_config_reads_back_the_same() {
+    let mut config = ExperimentConfig::default();
+    config.set("crossover", "blx:0.3").unwrap();
+
+    let stored: ExperimentConfig =
+        serde_json::from_str(&config.to_json()).unwrap();
+    assert_eq!(stored, config);
+}
+
+#[test]
+fn impossible_settings_are_invalid() {
+    let invalid = [
+        ("num_matches", "1000"),
+        ("num_agents_retained", "0"),
+        ("num_children_per_retained_agent", "10"),
+        ("crossover_rate", "1.5"),
+        ("max_perturb_amt", "0"),
+        ("perturb_decr", "0"),
+        ("cmaes_sigma", "-1"),
+        ("agent_limit", "depth:0"),
+        ("search_threads", "0"),
+        ("seed", "9223372036854775808"),
+    ];
+    for (key, value) in invalid {
+        let mut config = ExperimentConfig::default();
+        config.set(key, value).unwrap();
+        assert!(config.validate().is_err(), "{key}={value} passed");
+    }
+
+    // json has no infinity for set() to read, but a toml config can
+    let config: ExperimentConfig =
+        toml::from_str("max_perturb_amt = inf").unwrap();
+    assert!(config.validate().is_err(), "max_perturb_amt=inf passed");
+}
@@ -0,0 +1,350 @@
+use milestone::ai::heuristics::NUM_HEURISTICS;
+use milestone::ai::mcts::Rollout;
+use milestone::ai::tree::SearchLimit;
+use milestone::game::gamestate::GameBuilder;
+use milestone::game::player::{PossiblePlayer, AI, MCTS};
+use milestone::genetic::seed::{self, Stream};
+use milestone::genetic::{self, CmaEs, Genetic, Optimizer};
+use milestone::genetic::{
+    create_tables, Experiment, ExperimentConfig, DEFAULT_EXPERIMENT,
+};
+use milestone::genetic::{CODE_VERSION, DEFAULT_AGENT_LIMIT};
+use rusqlite::{params, Connection};
+
+use std::{env, fs};
+
+fn game_tables() -> Connection {
+    with_game_tables(Connection::open_in_memory().unwrap())
+}
+
+// the tables main() creates before the experiments'
+fn with_game_tables(conn: Connection) -> Connection {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE game_table (
+            game_id INTEGER PRIMARY KEY,
+            result INTEGER,
+            dataset TEXT,
+            experiment_id INTEGER
+        );
+        CREATE TABLE state_table (
+            state_id INTEGER PRIMARY KEY,
+            state TEXT NOT NULL,
+            move_number INTEGER,
+            game_id INTEGER
+        );
+        "#,
+    )
+    .unwrap();
+    conn
+}
+
+// agents with elos, stored the way batches store them
+fn agents(elos: &[i16]) -> Vec<u8> {
+    let agents: Vec<(AI, i16)> = elos
+        .iter()
+        .map(|&elo| {
+            let weights = [elo as f64 / 1000.0; NUM_HEURISTICS];
+            (AI::new(String::new(), weights, DEFAULT_AGENT_LIMIT), elo)
+        })
+        .collect();
+    bincode::serialize(&agents).unwrap()
+}
+
+fn push_batch(conn: &Connection, experiment: &Experiment, batch_id: u32) {
+    conn.execute(
+        r#"
+        INSERT INTO recovery_table (experiment_id, batch_id, agents)
+        VALUES (?1, ?2, ?3)
+        "#,
+        params![experiment.id, batch_id, agents(&[970, 1030])],
+    )
+    .unwrap();
+}
+
+fn push_game(conn: &Connection, experiment_id: Option<i64>) {
+    conn.execute(
+        "INSERT INTO game_table (result, experiment_id) VALUES (0, ?1)",
+        [experiment_id],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO state_table (state, game_id) VALUES ('b:', ?1)",
+        [conn.last_insert_rowid()],
+    )
+    .unwrap();
+}
+
+fn count(conn: &Connection, table: &str) -> u32 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+        row.get(0)
+    })
+    .unwrap()
+}
+
+#[test]
+fn experiments_keep_their_own_batches() {
+    let mut conn = game_tables();
+    create_tables(&mut conn).unwrap();
+
+    let config = ExperimentConfig::default();
+    let one = Experiment::create(&conn, "one", &config).unwrap();
+    let two = Experiment::create(&conn, "two", &config).unwrap();
+    push_batch(&conn, &one, 1);
+    push_batch(&conn, &one, 2);
+    push_batch(&conn, &two, 1);
+
+    assert_eq!(one.last_batch(&conn).unwrap().unwrap().batch_num, 2);
+    assert_eq!(two.last_batch(&conn).unwrap().unwrap().batch_num, 1);
+    assert_eq!(Experiment::find(&conn, "one").unwrap(), Some(one.clone()));
+    assert_eq!(one.code_version.as_deref(), Some(CODE_VERSION));
+    assert!(Experiment::create(&conn, "one", &config).is_err());
+
+    // agents come back from best to worst
+    let best = &one.last_batch(&conn).unwrap().unwrap().agents[0];
+    assert_eq!(best.weights[0], 1.03);
+}
+
+#[test]
+fn forks_carry_on_from_the_parents_last_batch() {
+    let mut conn = game_tables();
+    create_tables(&mut conn).unwrap();
+
+    let parent =
+        Experiment::create(&conn, "parent", &ExperimentConfig::default())
+            .unwrap();
+    push_batch(&conn, &parent, 1);
+    push_batch(&conn, &parent, 2);
+
+    let mut config = parent.config.clone();
+    config.set("optimizer", "cmaes").unwrap();
+    let fork = parent.fork(&conn, "fork", &config).unwrap();
+
+    assert_eq!(fork.parent_id, Some(parent.id));
+    assert_eq!(fork.num_batches(&conn).unwrap(), 1);
+    assert_eq!(fork.last_batch(&conn).unwrap().unwrap().batch_num, 2);
+    assert_eq!(
+        Experiment::find(&conn, "fork").unwrap().unwrap().config,
+        config
+    );
+}
+
+#[test]
+fn deleting_an_experiment_leaves_the_others() {
+    let mut conn = game_tables();
+    create_tables(&mut conn).unwrap();
+
+    let config = ExperimentConfig::default();
+    let doomed = Experiment::create(&conn, "doomed", &config).unwrap();
+    let kept = Experiment::create(&conn, "kept", &config).unwrap();
+    push_batch(&conn, &doomed, 1);
+    push_batch(&conn, &kept, 1);
+    push_game(&conn, Some(doomed.id));
+    push_game(&conn, Some(kept.id));
+    push_game(&conn, None);
+
+    doomed.delete(&mut conn).unwrap();
+
+    assert_eq!(Experiment::list(&conn).unwrap(), vec![kept.clone()]);
+    assert_eq!(count(&conn, "recovery_table"), 1);
+    assert_eq!(count(&conn, "game_table"), 2);
+    assert_eq!(count(&conn, "state_table"), 2);
+}
+
+#[test]
+fn batches_from_before_experiments_become_the_default_one() {
+    let mut conn = game_tables();
+    conn.execute_batch(
+        r#"
+        CREATE TABLE recovery_table (
+            batch_id INTEGER PRIMARY KEY,
+            agents BLOB,
+            timestamp DATETIME
+        );
+        INSERT INTO game_table (result, dataset) VALUES (0, 'selfplay');
+        "#,
+    )
+    .unwrap();
+    for batch_id in 1..=3 {
+        conn.execute(
+            "INSERT INTO recovery_table (batch_id, agents) VALUES (?1, ?2)",
+            params![batch_id, agents(&[1000, 1000])],
+        )
+        .unwrap();
+    }
+    push_game(&conn, None);
+
+    create_tables(&mut conn).unwrap();
+    // and only once
+    create_tables(&mut conn).unwrap();
+
+    let default = Experiment::find(&conn, DEFAULT_EXPERIMENT)
+        .unwrap()
+        .unwrap();
+    assert_eq!(default.num_batches(&conn).unwrap(), 3);
+    assert_eq!(default.code_version, None);
+    let scoped: u32 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM game_table WHERE experiment_id = ?1",
+            [default.id],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(scoped, 1);
+}
+
+// runs a new experiment, and returns what its batches left in recovery_table
+// (but the timestamps)
+fn run_batches(
+    conn: &Connection,
+    name: &str,
+    config: &ExperimentConfig,
+    optimizer: Box<dyn Optimizer>,
+) -> Vec<(u32, Vec<u8>, Vec<u8>, String)> {
+    let experiment = Experiment::create(conn, name, config).unwrap();
+    genetic::run(experiment.id, 1, optimizer, config);
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT batch_id, agents, optimizer, config FROM recovery_table
+            WHERE experiment_id = ?1 ORDER BY batch_id
+            "#,
+        )
+        .unwrap();
+    let rows = stmt
+        .query_map([experiment.id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })
+        .unwrap();
+    rows.collect::<rusqlite::Result<_>>().unwrap()
+}
+
+#[test]
+fn seeded_runs_play_out_the_same() {
+    // batches and games go to DATABASE_URL
+    let path = env::temp_dir()
+        .join(format!("milestone-seeded-{}.sqlite3", std::process::id()));
+    env::set_var("DATABASE_URL", &path);
+    let mut conn = with_game_tables(Connection::open(&path).unwrap());
+    create_tables(&mut conn).unwrap();
+
+    let mut config = ExperimentConfig::default();
+    for (key, value) in [
+        ("per_num_batches", "2"),
+        ("num_agents", "4"),
+        ("num_matches", "2"),
+        ("num_agents_retained", "2"),
+        ("agent_limit", "depth:1"),
+        ("crossover_rate", "0.5"),
+        ("seed", "7"),
+    ] {
+        config.set(key, value).unwrap();
+    }
+    let genetic = |config: &ExperimentConfig| -> Box<dyn Optimizer> {
+        Box::new(Genetic::new(1, None, config))
+    };
+    let cmaes = |config: &ExperimentConfig| -> Box<dyn Optimizer> {
+        Box::new(CmaEs::from_scratch(config))
+    };
+
+    let ga = run_batches(&conn, "ga", &config, genetic(&config));
+    assert_eq!(ga.len(), 2);
+    assert_eq!(
+        run_batches(&conn, "ga again", &config, genetic(&config)),
+        ga
+    );
+    assert_eq!(
+        run_batches(&conn, "cmaes", &config, cmaes(&config)),
+        run_batches(&conn, "cmaes again", &config, cmaes(&config))
+    );
+
+    config.seed = Some(8);
+    assert_ne!(
+        run_batches(&conn, "reseeded", &config, genetic(&config)),
+        ga
+    );
+
+    drop(conn);
+    fs::remove_file(path).unwrap();
+}
+
+// the opening of a game between MCTS players, which draw their playouts from
+// the rng of the match
+fn seeded_game(seed: u64, match_index: u64) -> Vec<String> {
+    let mcts = PossiblePlayer::MCTS(MCTS::new(
+        "mcts".to_string(),
+        [1.0; NUM_HEURISTICS],
+        SearchLimit::Iterations(20),
+        Rollout::Random,
+    ));
+    let mut game = GameBuilder::new()
+        .set_player_1(mcts.clone())
+        .set_player_2(mcts)
+        .build();
+    let mut rng = seed::rng(Some(seed), Stream::Match, 1, match_index);
+    while game.active && game.plies < 12 {
+        game.add_to_state_history();
+        game.play_one_turn_with_rng(&mut rng);
+    }
+    game.state_history
+}
+
+#[test]
+fn seeded_matches_replay_what_players_leave_to_chance() {
+    let game = seeded_game(7, 0);
+    assert_eq!(seeded_game(7, 0), game);
+    assert_ne!(seeded_game(7, 1), game);
+    assert_ne!(seeded_game(8, 0), game);
+}
+
+#[test]
+fn seeded_configs_cant_search_for_a_time_or_on_threads() {
+    let mut config = ExperimentConfig::default();
+    config.set("agent_limit", "time:100").unwrap();
+    assert!(config.validate().is_ok());
+    config.set("seed", "7").unwrap();
+    assert!(config.validate().is_err());
+    // or with several threads
+    let mut threaded = ExperimentConfig::default();
+    threaded.set("search_threads", "4").unwrap();
+    assert!(threaded.validate().is_ok());
+    threaded.set("seed", "7").unwrap();
+    assert!(threaded.validate().is_err());
+
+    // nor are they seeded when created
+    let mut conn = game_tables();
+    create_tables(&mut conn).unwrap();
+    config.seed = None;
+    let experiment = Experiment::create(&conn, "timed", &config).unwrap();
+    assert_eq!(experiment.seed, None);
+    threaded.seed = None;
+    let experiment = Experiment::create(&conn, "threaded", &threaded).unwrap();
+    assert_eq!(experiment.seed, None);
+
+    // while the rest search with one thread, and without the tablebase
+    let seeded =
+        Experiment::create(&conn, "seeded", &ExperimentConfig::default())
+            .unwrap();
+    let search = seeded.config.search_config();
+    assert!(seeded.seed.is_some());
+    assert_eq!(search.threads, 1);
+    assert!(!search.tablebase);
+}
+
+#[test]
+fn the_largest_valid_seed_can_be_stored() {
+    let mut conn = game_tables();
+    create_tables(&mut conn).unwrap();
+    let config = ExperimentConfig {
+        seed: Some(i64::MAX as u64),
+        ..ExperimentConfig::default()
+    };
+    assert!(config.validate().is_ok());
+    let experiment = Experiment::create(&conn, "largest", &config).unwrap();
+    assert_eq!(experiment.seed, config.seed);
+    assert_eq!(
+        Experiment::find(&conn, "largest").unwrap(),
+        Some(experiment)
+    );
+}
@@ -0,0 +1,165 @@
+use milestone::ai::export::{load_split, write_npy, ExportOptions, ExportRow};
+use milestone::game::gamestate::{GameBuilder, State};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rusqlite::{params, Connection};
+
+use std::collections::HashSet;
+use std::{env, fs, process};
+
+// a database of `games` random games, each with its states from the
+// start, in the tables main() creates
+fn random_games(games: usize, seed: u64) -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        r#"
+        CREATE TABLE game_table (
+            game_id INTEGER PRIMARY KEY,
+            result INTEGER,
+            dataset TEXT,
+            experiment_id INTEGER
+        );
+        CREATE TABLE state_table (
+            state_id INTEGER PRIMARY KEY,
+            state TEXT NOT NULL,
+            move_number INTEGER,
+            game_id INTEGER
+        );
+        "#,
+    )
+    .unwrap();
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..games {
+        conn.execute(
+            "INSERT INTO game_table (result) VALUES (?1)",
+            [rng.gen_range(0..2)],
+        )
+        .unwrap();
+        let game_id = conn.last_insert_rowid();
+        let mut state = GameBuilder::new().build();
+        for move_number in 0..12 {
+            conn.execute(
+                "INSERT INTO state_table (state, move_number, game_id) \
+                 VALUES (?1, ?2, ?3)",
+                params![state.to_repr_string(), move_number, game_id],
+            )
+            .unwrap();
+            let moves = state.current_possible_moves(state.current_turn);
+            if moves.is_empty() {
+                break;
+            }
+            state.make_move(moves[rng.gen_range(0..moves.len())]);
+        }
+    }
+    conn
+}
+
+fn reprs(rows: &[ExportRow]) -> HashSet<String> {
+    rows.iter().map(|row| row.state.clone()).collect()
+}
+
+// the reprs of every state of a game
+fn reprs_of_game(conn: &Connection, game_id: i64) -> HashSet<String> {
+    let mut stmt = conn
+        .prepare("SELECT state FROM state_table WHERE game_id = ?1")
+        .unwrap();
+    let states = stmt.query_map([game_id], |row| row.get(0)).unwrap();
+    states.map(|s| s.unwrap()).collect()
+}
+
+#[test]
+fn games_stay_on_one_side_of_the_split() {
+    let conn = random_games(40, 1);
+    let (train, test) = load_split(
+        &conn,
+        None,
+        0.25,
+        ExportOptions::default(),
+        &mut StdRng::seed_from_u64(2),
+    )
+    .unwrap();
+    assert!(!train.is_empty() && !test.is_empty());
+
+    // a game split across both sides would leave some of its positions out
+    // of each
+    let (train_reprs, test_reprs) = (reprs(&train), reprs(&test));
+    for game_id in 1..=40 {
+        let positions = reprs_of_game(&conn, game_id);
+        assert!(
+            positions.is_subset(&train_reprs)
+                || positions.is_subset(&test_reprs),
+            "game {game_id} is on both sides"
+        );
+    }
+
+    // and every game counts once, on one side, towards the start position
+    let start = GameBuilder::new().build().to_repr_string();
+    let start_games: u32 = train
+        .iter()
+        .chain(&test)
+        .filter(|row| row.state == start)
+        .map(|row| row.games)
+        .sum();
+    assert_eq!(start_games, 40);
+    let test_games = test.iter().find(|row| row.state == start).unwrap();
+    assert_eq!(test_games.games, 10);
+}
+
+#[test]
+fn mirroring_adds_reflected_positions() {
+    let conn = random_games(10, 3);
+    let split = |mirror| {
+        let options = ExportOptions {
+            mirror,
+            ..ExportOptions::default()
+        };
+        let mut rng = StdRng::seed_from_u64(4);
+        load_split(&conn, None, 0.0, options, &mut rng).unwrap().0
+    };
+    let plain = reprs(&split(false));
+    let mirrored = reprs(&split(true));
+
+    assert!(plain.is_subset(&mirrored));
+    assert!(mirrored.len() > plain.len());
+    for repr in &plain {
+        let state = State::from_repr_string(repr).unwrap();
+        assert!(mirrored.contains(&state.mirror().to_repr_string()));
+    }
+    // nothing else is added
+    for repr in mirrored.difference(&plain) {
+        let state = State::from_repr_string(repr).unwrap();
+        assert!(plain.contains(&state.mirror().to_repr_string()));
+    }
+}
+
+#[test]
+fn npy_files_have_a_padded_header_and_every_value() {
+    let conn = random_games(5, 5);
+    let options = ExportOptions::default();
+    let (rows, _) =
+        load_split(&conn, None, 0.0, options, &mut StdRng::seed_from_u64(6))
+            .unwrap();
+    let path =
+        env::temp_dir().join(format!("milestone-export-{}.npy", process::id()));
+    write_npy(&path, &rows, options).unwrap();
+    let bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let data_start = 10 + header_len;
+    assert_eq!(data_start % 64, 0);
+
+    let header = std::str::from_utf8(&bytes[10..data_start]).unwrap();
+    assert!(header.ends_with('\n'));
+    // the 37 hexes, the number of games and the target
+    let columns = 37 + 2;
+    assert!(header.contains(&format!("'shape': ({}, {columns})", rows.len())));
+    assert_eq!(bytes.len() - data_start, rows.len() * columns * 8);
+
+    // the last value of the first row is its target
+    let target = &bytes[data_start + (columns - 1) * 8..][..8];
+    let target = f64::from_le_bytes(target.try_into().unwrap());
+    assert_eq!(target, rows[0].black_win_ratio());
+}
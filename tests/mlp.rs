@@ -0,0 +1,31 @@
+use milestone::ai::mlp::{encode, Mlp};
+use milestone::game::gamestate::State;
+use serde_json::Value;
+
+use std::fs;
+
+// tests/data/mlp_parity.json holds a small model in the format of
+// neuralnet/export.py along with what MLPRegressor predicts for a few
+// positions (refreshed by neuralnet/parity.py)
+const FIXTURE: &str = "tests/data/mlp_parity.json";
+
+#[test]
+fn predictions_match_sklearn() {
+    let fixture: Value =
+        serde_json::from_str(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    let mlp: Mlp = serde_json::from_value(fixture["model"].clone()).unwrap();
+
+    let cases = fixture["cases"].as_array().unwrap();
+    assert!(!cases.is_empty());
+    for case in cases {
+        let repr = case["state"].as_str().unwrap();
+        let expected = case["prediction"].as_f64().unwrap();
+        let state = State::from_repr_string(repr).unwrap();
+
+        let predicted = mlp.predict(&encode(&state.board));
+        assert!(
+            (predicted - expected).abs() < 1e-9,
+            "{repr}: predicted {predicted}, sklearn gives {expected}"
+        );
+    }
+}
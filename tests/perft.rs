@@ -0,0 +1,82 @@
+use milestone::game::gamestate::{GameBuilder, State};
+use milestone::game::perft::{divide, perft, PerftCounts};
+
+// (nodes, captures, wins) at depths 1 to 5
+type Reference = [(u64, u64, u64); 5];
+
+const START: Reference = [
+    (13, 0, 0),
+    (169, 0, 0),
+    (2418, 4, 0),
+    (34538, 197, 0),
+    (530245, 5876, 0),
+];
+
+const POSITIONS: [(&str, Reference); 2] = [
+    (
+        "w:b/b1/b1b/1b1b/1b1/4/3/1w2/3/w2w/www/ww/w",
+        [
+            (16, 0, 0),
+            (223, 2, 0),
+            (3630, 52, 0),
+            (52063, 1153, 0),
+            (859637, 20708, 0),
+        ],
+    ),
+    (
+        "b:1/2/3/4/1b1/2w1/1b1/4/w2/4/3/2/1",
+        [
+            (4, 0, 0),
+            (20, 0, 0),
+            (102, 1, 0),
+            (542, 7, 0),
+            (2879, 37, 26),
+        ],
+    ),
+];
+
+fn counts((nodes, captures, wins): (u64, u64, u64)) -> PerftCounts {
+    PerftCounts {
+        nodes,
+        captures,
+        wins,
+    }
+}
+
+#[test]
+fn start_position_matches_reference_counts() {
+    let mut state = GameBuilder::new().build();
+    for (depth, &expected) in START.iter().enumerate() {
+        assert_eq!(perft(&mut state, depth + 1), counts(expected));
+    }
+    assert_eq!(perft(&mut state, 0), counts((1, 0, 0)));
+}
+
+#[test]
+fn positions_match_reference_counts() {
+    for (repr, expected) in POSITIONS {
+        let mut state = State::from_repr_string(repr).unwrap();
+        for (depth, &expected) in expected.iter().enumerate() {
+            assert_eq!(
+                perft(&mut state, depth + 1),
+                counts(expected),
+                "perft({}) of {repr}",
+                depth + 1
+            );
+        }
+        // perft leaves the position as it found it
+        assert_eq!(state.to_repr_string(), repr);
+    }
+}
+
+#[test]
+fn divide_adds_up_to_perft() {
+    for (repr, _) in POSITIONS {
+        let mut state = State::from_repr_string(repr).unwrap();
+        let mut total = PerftCounts::default();
+        for (_, counts) in divide(&mut state, 3) {
+            total += counts;
+        }
+        assert_eq!(total, perft(&mut state, 3));
+    }
+}
@@ -0,0 +1,195 @@
+use milestone::ai::heuristics::HeuristicWeights;
+use milestone::game::board::{
+    color_flipped_hex, get_moves_of_piece, Move, MIRRORED_HEXES,
+};
+use milestone::game::gamestate::{GameBuilder, State};
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+// every piece only moves forward, one row at a time diagonally or two
+// straight ahead, so no game can outlast ten pieces a side walking all
+// twelve rows
+const MAX_PLIES: usize = 2 * 10 * 12;
+
+// a random game, as the index of the move played at every ply. games that
+// end early ignore the rest
+fn playout() -> impl Strategy<Value = Vec<Index>> {
+    prop::collection::vec(any::<Index>(), 0..MAX_PLIES)
+}
+
+fn play(choices: &[Index]) -> Vec<State> {
+    let mut state = GameBuilder::new().build();
+    let mut states = vec![state.clone()];
+    for choice in choices {
+        let moves = state.current_possible_moves(state.current_turn);
+        if moves.is_empty() {
+            break;
+        }
+        state.make_move(*choice.get(&moves));
+        states.push(state.clone());
+    }
+    states
+}
+
+#[test]
+fn taking_back_a_move_drops_its_position_from_the_history() {
+    let mut state = GameBuilder::new().build();
+    state.add_to_state_history();
+    let m = state.current_possible_moves(state.current_turn)[0];
+    state.move_piece(m.origin(), m.dest(), true).unwrap();
+    state.add_to_state_history();
+
+    let undo = state.last_move.unwrap();
+    assert_eq!(undo.mv, m);
+    state.take_back(undo);
+    assert_eq!(
+        state.state_history,
+        vec![GameBuilder::new().build().to_repr_string()]
+    );
+    assert_eq!(state.current_turn, 0);
+    assert!(state.last_move.is_none());
+}
+
+#[test]
+fn move_tables_are_symmetric() {
+    for (hex, &mirror) in MIRRORED_HEXES.iter().enumerate() {
+        let black = get_moves_of_piece(0, &hex);
+
+        // a half turn takes black's moves to white's
+        let white = get_moves_of_piece(1, &color_flipped_hex(hex));
+        let flip = |hexes: &[usize]| {
+            let mut flipped: Vec<usize> =
+                hexes.iter().map(|&h| color_flipped_hex(h)).collect();
+            flipped.sort();
+            flipped
+        };
+        let diagonal: Vec<usize> = black.diagonal.iter().collect();
+        let straight: Vec<usize> = black.straight.iter().collect();
+        assert_eq!(flip(&diagonal), white.diagonal.iter().collect::<Vec<_>>());
+        assert_eq!(flip(&straight), white.straight.iter().collect::<Vec<_>>());
+
+        // and the left-right reflection takes black's moves to black's
+        let mirrored = get_moves_of_piece(0, &mirror);
+        let mut reflected: Vec<usize> =
+            diagonal.iter().map(|&h| MIRRORED_HEXES[h]).collect();
+        reflected.sort();
+        assert_eq!(reflected, mirrored.diagonal.iter().collect::<Vec<_>>());
+        assert_eq!(
+            straight
+                .iter()
+                .map(|&h| MIRRORED_HEXES[h])
+                .collect::<Vec<_>>(),
+            mirrored.straight.iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+// the original move lists gave hex 7 the moves of hexes 8 and 9
+// (`Diagonal(8, 11)` and `Straight(9, 14)`)
+#[test]
+fn hex_7_moves_from_hex_7() {
+    let state =
+        State::from_repr_string("b:1/2/3/1b2/3/4/3/4/3/4/3/2/w").unwrap();
+    let mut moves: Vec<String> = state
+        .current_possible_moves(0)
+        .iter()
+        .map(|m| format!("{m:?}"))
+        .collect();
+    moves.sort();
+    assert_eq!(
+        moves,
+        ["Diagonal(7, 10)", "Diagonal(7, 11)", "Straight(7, 14)"]
+    );
+}
+
+#[test]
+fn pieces_only_move_forward() {
+    for hex in 0..37 {
+        let black = get_moves_of_piece(0, &hex);
+        let white = get_moves_of_piece(1, &hex);
+        assert!((black.diagonal | black.straight).iter().all(|h| h > hex));
+        assert!((white.diagonal | white.straight).iter().all(|h| h < hex));
+    }
+}
+
+proptest! {
+    #[test]
+    fn possible_moves_are_accepted_by_move_piece(choices in playout()) {
+        for state in play(&choices) {
+            let turn = state.current_turn;
+            let own = state.board.current_players_pieces(turn);
+            let other = state.board.current_players_pieces(1 - turn);
+            for m in state.current_possible_moves(turn) {
+                prop_assert!(own.contains(m.origin()));
+                let mut moved = state.clone();
+                let played = moved.move_piece(m.origin(), m.dest(), true);
+                prop_assert_eq!(played, Ok(()));
+                prop_assert!(moved.board != state.board);
+
+                // captures only happen head-on
+                let captured = other.contains(m.dest());
+                prop_assert!(!captured || matches!(m, Move::Straight(..)));
+            }
+        }
+    }
+
+    #[test]
+    fn repr_strings_round_trip(choices in playout()) {
+        for state in play(&choices).iter().filter(|state| state.active) {
+            let repr = state.to_repr_string();
+            let read = State::from_repr_string(&repr).unwrap();
+            prop_assert!(read.board == state.board);
+            prop_assert_eq!(read.current_turn, state.current_turn);
+            prop_assert_eq!(read.to_repr_string(), repr);
+        }
+    }
+
+    #[test]
+    fn games_terminate(
+        choices in prop::collection::vec(any::<Index>(), MAX_PLIES),
+    ) {
+        let states = play(&choices);
+        let end = states.last().unwrap();
+        prop_assert!(!end.active, "still going after {} plies", MAX_PLIES);
+        prop_assert!(end.current_possible_moves(end.current_turn).is_empty());
+
+        // the winner is always whoever moved last
+        let last_mover = states[states.len() - 2].current_turn;
+        prop_assert_eq!(end.winner, Some(last_mover));
+    }
+
+    #[test]
+    fn unmaking_a_move_restores_the_position(choices in playout()) {
+        for state in play(&choices) {
+            for m in state.current_possible_moves(state.current_turn) {
+                let mut moved = state.clone();
+                let undo = moved.make_move(m);
+                moved.unmake_move(undo);
+                prop_assert!(moved.board == state.board);
+                prop_assert_eq!(moved.active, state.active);
+                prop_assert_eq!(moved.winner, state.winner);
+                prop_assert_eq!(moved.current_turn, state.current_turn);
+            }
+        }
+    }
+
+    #[test]
+    fn score_is_color_antisymmetric(
+        choices in playout(),
+        weights in prop::array::uniform19(-10.0..10.0f64),
+    ) {
+        let heuristics = HeuristicWeights::new(weights);
+        // every heuristic rounds down, so can be one off once flipped
+        let tolerance: f64 = weights.iter().map(|w| w.abs()).sum::<f64>() + 1e-6;
+        for state in play(&choices) {
+            let total =
+                heuristics.score(&state) + heuristics.score(&state.color_flip());
+            prop_assert!(
+                total.abs() <= tolerance,
+                "{} is off by {}",
+                state.to_repr_string(),
+                total
+            );
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use milestone::ai::heuristics::NUM_HEURISTICS;
+use milestone::ai::search::{SearchConfig, Searcher};
+use milestone::ai::tree::SearchLimit;
+use milestone::game::gamestate::GameBuilder;
+
+#[test]
+fn a_depth_of_zero_still_searches_a_ply() {
+    let state = GameBuilder::new().build();
+    let suggested = Searcher::new(&[1.0; NUM_HEURISTICS])
+        .search(&state, &SearchLimit::Depth(0));
+
+    assert_eq!(suggested.max_depth_considered(), 1);
+    assert!(state
+        .current_possible_moves(0)
+        .contains(&suggested.suggestion));
+}
+
+#[test]
+fn parallel_searches_play_move_after_move() {
+    let config = SearchConfig {
+        threads: 3,
+        ..SearchConfig::default()
+    };
+    let mut state = GameBuilder::new().build();
+    // with the same pool of threads for every move
+    for _ in 0..4 {
+        let suggested = Searcher::with_config(&[1.0; NUM_HEURISTICS], config)
+            .search(&state, &SearchLimit::Depth(3));
+        state.make_move(suggested.suggestion);
+    }
+    assert_eq!(state.plies, 4);
+}
@@ -0,0 +1,21 @@
+use milestone::genetic::Noise;
+
+#[test]
+fn noise_parses_only_within_its_range() {
+    assert_eq!("none".parse(), Ok(Noise::None));
+    assert_eq!("epsilon:0.25".parse(), Ok(Noise::Epsilon(0.25)));
+    assert_eq!("temperature:2".parse(), Ok(Noise::Temperature(2.0)));
+
+    for spec in [
+        "epsilon:-0.1",
+        "epsilon:1.5",
+        "epsilon:NaN",
+        "temperature:0",
+        "temperature:-1",
+        "temperature:inf",
+        "temperature",
+        "gaussian:1",
+    ] {
+        assert!(spec.parse::<Noise>().is_err(), "{spec} parsed");
+    }
+}
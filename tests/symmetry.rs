@@ -0,0 +1,92 @@
+use milestone::ai::heuristics::{HeuristicWeights, NUM_HEURISTICS};
+use milestone::game::board::Move;
+use milestone::game::gamestate::{GameBuilder, State};
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// a position reached by playing `plies` random moves from the start (or
+// fewer, if the game ends first)
+fn random_position(seed: u64, plies: usize) -> State {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut state = GameBuilder::new().build();
+    for _ in 0..plies {
+        let moves = state.current_possible_moves(state.current_turn);
+        if moves.is_empty() {
+            break;
+        }
+        state.make_move(moves[rng.gen_range(0..moves.len())]);
+    }
+    state
+}
+
+fn sorted_moves(state: &State, f: impl Fn(&Move) -> Move) -> Vec<String> {
+    let mut moves: Vec<String> = state
+        .current_possible_moves(state.current_turn)
+        .iter()
+        .map(|m| format!("{:?}", f(m)))
+        .collect();
+    moves.sort();
+    moves
+}
+
+proptest! {
+    #[test]
+    fn symmetries_are_involutions(seed: u64, plies in 0..80usize) {
+        let state = random_position(seed, plies);
+        prop_assert!(state.board.mirror().mirror() == state.board);
+        prop_assert!(state.board.color_flip().color_flip() == state.board);
+        prop_assert_eq!(
+            state.color_flip().color_flip().to_repr_string(),
+            state.to_repr_string()
+        );
+    }
+
+    #[test]
+    fn moves_map_under_symmetries(seed: u64, plies in 0..80usize) {
+        let state = random_position(seed, plies);
+        prop_assert_eq!(
+            sorted_moves(&state, Move::mirror),
+            sorted_moves(&state.mirror(), |m| *m)
+        );
+        prop_assert_eq!(
+            sorted_moves(&state, Move::color_flip),
+            sorted_moves(&state.color_flip(), |m| *m)
+        );
+    }
+
+    #[test]
+    fn mirror_images_share_a_canonical_form(
+        seed: u64,
+        plies in 0..80usize,
+    ) {
+        let board = random_position(seed, plies).board;
+        prop_assert!(board.canonical() == board.mirror().canonical());
+        prop_assert!(
+            board.canonical() == board || board.canonical() == board.mirror()
+        );
+    }
+
+    #[test]
+    fn heuristics_are_antisymmetric_under_color_flip(
+        seed: u64,
+        plies in 0..80usize,
+    ) {
+        let state = random_position(seed, plies);
+        let flipped = state.color_flip();
+        let heuristics = HeuristicWeights::new([1.0; NUM_HEURISTICS]);
+
+        let scores = heuristics.feature_scores(&state);
+        let flipped_scores = heuristics.feature_scores(&flipped);
+        for (name, (score, flipped_score)) in heuristics
+            .feature_names()
+            .iter()
+            .zip(scores.iter().zip(flipped_scores))
+        {
+            // normalized by rounding down, so flipping can be one off
+            prop_assert!((score + flipped_score).abs() <= 1, "{}", name);
+        }
+        let total = heuristics.score(&state) + heuristics.score(&flipped);
+        prop_assert!(total.abs() <= NUM_HEURISTICS as f64);
+    }
+}
@@ -0,0 +1,25 @@
+use milestone::ai::tablebase::Tablebase;
+
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::{env, process};
+
+#[test]
+fn tablebases_of_the_wrong_size_dont_load() {
+    let path =
+        env::temp_dir().join(format!("milestone-tb-{}.bin", process::id()));
+    Tablebase::generate(1).save(&path).unwrap();
+    assert_eq!(Tablebase::load(&path).unwrap().max_pieces(), 1);
+
+    let len = fs::metadata(&path).unwrap().len();
+    let file = OpenOptions::new().write(true).open(&path).unwrap();
+    file.set_len(len - 1).unwrap();
+    let truncated = Tablebase::load(&path).map(|_| ()).unwrap_err();
+    assert_eq!(truncated.kind(), ErrorKind::InvalidData);
+
+    file.set_len(len + 1).unwrap();
+    let padded = Tablebase::load(&path).map(|_| ()).unwrap_err();
+    assert_eq!(padded.kind(), ErrorKind::InvalidData);
+
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,192 @@
+use milestone::ai::mlp::{encode, Activation, Mlp};
+use milestone::ai::training::Sample;
+use milestone::ai::training::{gradients, load_samples, train, TrainConfig};
+use milestone::game::bitboard::NUM_HEXES;
+use milestone::game::gamestate::{GameBuilder, State};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rusqlite::{params, Connection};
+
+// a position reached by playing `plies` random moves from the start
+fn random_position<R: Rng>(rng: &mut R, plies: usize) -> State {
+    let mut state = GameBuilder::new().build();
+    for _ in 0..plies {
+        let moves = state.current_possible_moves(state.current_turn);
+        if moves.is_empty() {
+            break;
+        }
+        state.make_move(moves[rng.gen_range(0..moves.len())]);
+    }
+    state
+}
+
+fn sample(state: &State, target: f64) -> Sample {
+    Sample {
+        state: state.to_repr_string(),
+        input: encode(&state.board),
+        target,
+    }
+}
+
+// what gradients() differentiates: half the mean squared error plus the
+// L2 penalty on the coefs
+fn loss(mlp: &Mlp, batch: &[&Sample], l2: f64) -> f64 {
+    let errors: f64 = batch
+        .iter()
+        .map(|s| (mlp.predict(&s.input) - s.target).powi(2) / 2.0)
+        .sum();
+    let penalty: f64 =
+        mlp.coefs.iter().flatten().flatten().map(|w| w * w).sum();
+    (errors + l2 * penalty / 2.0) / batch.len() as f64
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mlp = Mlp {
+        activation: Activation::Tanh,
+        out_activation: Activation::Identity,
+        coefs: vec![
+            (0..NUM_HEXES)
+                .map(|_| (0..3).map(|_| rng.gen_range(-0.3..0.3)).collect())
+                .collect(),
+            (0..3).map(|_| vec![rng.gen_range(-0.5..0.5)]).collect(),
+        ],
+        intercepts: vec![
+            (0..3).map(|_| rng.gen_range(-0.1..0.1)).collect(),
+            vec![0.2],
+        ],
+    };
+    let samples: Vec<Sample> = (0..4)
+        .map(|i| sample(&random_position(&mut rng, 4 + 3 * i), i as f64 / 3.0))
+        .collect();
+    let batch: Vec<&Sample> = samples.iter().collect();
+    let config = TrainConfig {
+        activation: Activation::Tanh,
+        l2: 0.01,
+        ..TrainConfig::default()
+    };
+
+    let (coef_grads, intercept_grads) = gradients(&mlp, &batch, &config);
+    // the central difference of the loss as `nudge` moves one parameter
+    let numeric = |nudge: &dyn Fn(&mut Mlp, f64)| {
+        let h = 1e-6;
+        let (mut up, mut down) = (mlp.clone(), mlp.clone());
+        nudge(&mut up, h);
+        nudge(&mut down, -h);
+        (loss(&up, &batch, config.l2) - loss(&down, &batch, config.l2))
+            / (2.0 * h)
+    };
+    let close = |analytic: f64, numeric: f64| {
+        assert!(
+            (analytic - numeric).abs() < 1e-6,
+            "backprop gives {analytic}, finite differences {numeric}"
+        );
+    };
+    for (layer, grads) in coef_grads.iter().enumerate() {
+        for (i, grads) in grads.iter().enumerate() {
+            for (j, &grad) in grads.iter().enumerate() {
+                close(grad, numeric(&|m, d| m.coefs[layer][i][j] += d));
+            }
+        }
+    }
+    for (layer, grads) in intercept_grads.iter().enumerate() {
+        for (j, &grad) in grads.iter().enumerate() {
+            close(grad, numeric(&|m, d| m.intercepts[layer][j] += d));
+        }
+    }
+}
+
+#[test]
+fn training_lowers_the_loss() {
+    let mut rng = StdRng::seed_from_u64(5);
+    // the target is the share of pieces that are black, which a network can
+    // learn from the encoding
+    let samples: Vec<Sample> = (0..200)
+        .map(|_| {
+            let plies = rng.gen_range(0..40);
+            let state = random_position(&mut rng, plies);
+            let black = state.board.current_players_pieces(0).count() as f64;
+            let white = state.board.current_players_pieces(1).count() as f64;
+            sample(&state, black / (black + white))
+        })
+        .collect();
+    let config = TrainConfig {
+        hidden_layers: vec![8],
+        epochs: 30,
+        batch_size: 20,
+        learning_rate: 0.01,
+        ..TrainConfig::default()
+    };
+
+    let (_, losses) = train(&samples, &config, &mut rng);
+    assert_eq!(losses.len(), config.epochs);
+    let (first, last) = (losses[0], losses[losses.len() - 1]);
+    assert!(last.train < first.train / 2.0, "{first:?} to {last:?}");
+    assert!(last.validation < first.validation, "{first:?} to {last:?}");
+}
+
+#[test]
+fn samples_target_the_share_of_games_black_won() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        r#"
+        CREATE TABLE game_table (
+            game_id INTEGER PRIMARY KEY,
+            result INTEGER,
+            dataset TEXT,
+            experiment_id INTEGER
+        );
+        CREATE TABLE state_table (
+            state_id INTEGER PRIMARY KEY,
+            state TEXT NOT NULL,
+            move_number INTEGER,
+            game_id INTEGER
+        );
+        "#,
+    )
+    .unwrap();
+
+    let start = GameBuilder::new().build().to_repr_string();
+    let later = random_position(&mut StdRng::seed_from_u64(1), 6);
+    let later = later.to_repr_string();
+    // a result of 0 is a win for black
+    let games = [
+        (0, "a", vec![&start, &later]),
+        (1, "a", vec![&start]),
+        (1, "a", vec![&start, &later]),
+        (0, "b", vec![&start]),
+    ];
+    for (result, dataset, states) in games {
+        conn.execute(
+            "INSERT INTO game_table (result, dataset) VALUES (?1, ?2)",
+            params![result, dataset],
+        )
+        .unwrap();
+        let game_id = conn.last_insert_rowid();
+        for state in states {
+            conn.execute(
+                "INSERT INTO state_table (state, game_id) VALUES (?1, ?2)",
+                params![state, game_id],
+            )
+            .unwrap();
+        }
+    }
+
+    let target = |samples: &[Sample], state: &str| {
+        let found = samples.iter().find(|s| s.state == state).unwrap();
+        assert_eq!(
+            found.input,
+            encode(&State::from_repr_string(state).unwrap().board)
+        );
+        found.target
+    };
+    let samples = load_samples(&conn, None).unwrap();
+    assert_eq!(samples.len(), 2);
+    assert!((target(&samples, &start) - 0.5).abs() < 1e-12);
+    assert!((target(&samples, &later) - 0.5).abs() < 1e-12);
+
+    let samples = load_samples(&conn, Some("a")).unwrap();
+    assert_eq!(samples.len(), 2);
+    assert!((target(&samples, &start) - 1.0 / 3.0).abs() < 1e-12);
+}
@@ -0,0 +1,89 @@
+use milestone::game::board::Move::{Diagonal, Straight};
+use milestone::game::gamestate::{GameBuilder, State};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn assert_hash_matches_scratch(state: &State) {
+    assert_eq!(
+        state.board.hash(),
+        state.board.compute_hash(),
+        "incremental hash diverged at {}",
+        state.board.to_repr_string()
+    );
+}
+
+#[test]
+fn incremental_hash_matches_scratch_over_random_games() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..200 {
+        let mut state = GameBuilder::new().build();
+        let mut undos = vec![];
+        let mut hashes = vec![];
+
+        while state.active {
+            let moves = state.current_possible_moves(state.current_turn);
+            let m = moves[rng.gen_range(0..moves.len())];
+
+            hashes.push(state.hash());
+            undos.push(state.make_move(m));
+            assert_hash_matches_scratch(&state);
+        }
+
+        // unmaking every move restores every hash along the way
+        while let Some(undo) = undos.pop() {
+            state.unmake_move(undo);
+            assert_hash_matches_scratch(&state);
+            assert_eq!(state.hash(), hashes.pop().unwrap());
+        }
+    }
+}
+
+#[test]
+fn move_piece_updates_hash() {
+    let mut state = GameBuilder::new().build();
+    for (from, to) in [(7, 14), (28, 21), (14, 21)] {
+        state.move_piece(from, to, true).unwrap();
+        assert_hash_matches_scratch(&state);
+    }
+}
+
+#[test]
+fn parsed_positions_match_scratch_and_played_positions() {
+    let mut played = GameBuilder::new().build();
+    for m in [Diagonal(7, 11), Straight(31, 24)] {
+        played.make_move(m);
+    }
+
+    let parsed = State::from_repr_string(&played.to_repr_string()).unwrap();
+    assert_hash_matches_scratch(&parsed);
+    assert_eq!(parsed.hash(), played.hash());
+}
+
+#[test]
+fn transpositions_share_a_hash() {
+    let mut first = GameBuilder::new().build();
+    let mut second = GameBuilder::new().build();
+
+    for m in [Diagonal(7, 11), Straight(31, 24), Diagonal(8, 12)] {
+        first.make_move(m);
+    }
+    for m in [Diagonal(8, 12), Straight(31, 24), Diagonal(7, 11)] {
+        second.make_move(m);
+    }
+
+    assert_eq!(first.hash(), second.hash());
+}
+
+#[test]
+fn side_to_move_changes_hash() {
+    let black =
+        State::from_repr_string("b:b/bb/bbb/bbbb/3/4/3/4/3/wwww/www/ww/w")
+            .unwrap();
+    let white =
+        State::from_repr_string("w:b/bb/bbb/bbbb/3/4/3/4/3/wwww/www/ww/w")
+            .unwrap();
+
+    assert_eq!(black.board.hash(), white.board.hash());
+    assert_ne!(black.hash(), white.hash());
+}